@@ -3,6 +3,7 @@ use inner_products::{
     InnerProduct,
     MultiexponentiationInnerProduct,
     PairingInnerProduct,
+    ReverseMultiexponentiationInnerProduct,
 };
 
 use rand::{rngs::StdRng, SeedableRng, Rng};
@@ -39,4 +40,7 @@ fn main() {
 
     println!("3) Multiexponentiation G2 inner product...");
     bench_inner_product::<MultiexponentiationInnerProduct<<Bls12_381 as PairingEngine>::G2Projective>, StdRng>(&mut rng, LEN);
+
+    println!("4) Reverse multiexponentiation G2 inner product...");
+    bench_inner_product::<ReverseMultiexponentiationInnerProduct<<Bls12_381 as PairingEngine>::G2Projective>, StdRng>(&mut rng, LEN);
 }
\ No newline at end of file
@@ -1,6 +1,10 @@
 use algebra::{
+    bls12_377::Bls12_377,
     bls12_381::Bls12_381,
-    curves::PairingEngine,
+    bn254::Bn254,
+    mnt4_298::MNT4_298,
+    mnt6_298::MNT6_298,
+    curves::{AffineCurve, PairingEngine, ProjectiveCurve},
     UniformRand,
     groups::Group,
     fields::Field
@@ -10,6 +14,7 @@ use inner_products::{
     InnerProduct,
     MultiexponentiationInnerProduct,
     PairingInnerProduct,
+    ReverseMultiexponentiationInnerProduct,
 };
 use dh_commitments::{
     DoublyHomomorphicCommitment,
@@ -18,8 +23,9 @@ use dh_commitments::{
     pedersen::PedersenCommitment,
 };
 use ip_proofs::tipa::{
-    TIPA, TIPACompatibleSetup,
-    structured_scalar_message::structured_scalar_power,
+    G1Placement, G2Placement, KeyPlacement, TIPA, Trapdoor,
+    structured_generators_message::TIPAWithSGM,
+    structured_scalar_message::{structured_scalar_power, TIPAWithSSM},
 };
 
 use rand::{rngs::StdRng, SeedableRng, Rng};
@@ -31,6 +37,8 @@ use std::{
     ops::MulAssign,
 };
 
+const NUM_REPEATED_PROOFS: usize = 4;
+
 fn bench_tipa<IP, LMC, RMC, IPC, P, D, R: Rng>(rng: &mut R, len: usize)
     where
         D: Digest,
@@ -40,9 +48,8 @@ fn bench_tipa<IP, LMC, RMC, IPC, P, D, R: Rng>(rng: &mut R, len: usize)
             RightMessage = RMC::Message,
             Output = IPC::Message,
         >,
-        LMC: DoublyHomomorphicCommitment<Scalar = P::Fr, Key = P::G2Projective> + TIPACompatibleSetup,
-        RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar, Key = P::G1Projective>
-        + TIPACompatibleSetup,
+        LMC: DoublyHomomorphicCommitment<Scalar = P::Fr, Key = P::G2Projective>,
+        RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar, Key = P::G1Projective>,
         IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
         LMC::Message: MulAssign<P::Fr>,
         RMC::Message: MulAssign<P::Fr>,
@@ -73,6 +80,33 @@ fn bench_tipa<IP, LMC, RMC, IPC, P, D, R: Rng>(rng: &mut R, len: usize)
         let proof = TIPA::<IP, LMC, RMC, IPC, P, D>::prove(&srs, (&l, &r), (&ck_l, &ck_r, &ck_t)).unwrap();
         let mut bench = start.elapsed().as_millis();
         println!("\t proving time: {} ms", bench);
+        // The SRS is kept in affine form, so proving again with it does not normalize it again
+        start = Instant::now();
+        for _ in 0..NUM_REPEATED_PROOFS {
+            TIPA::<IP, LMC, RMC, IPC, P, D>::prove(&srs, (&l, &r), (&ck_l, &ck_r, &ck_t)).unwrap();
+        }
+        bench = start.elapsed().as_millis() / NUM_REPEATED_PROOFS as u128;
+        println!("\t repeated proving time: {} ms per proof", bench);
+        // Baseline normalizing the powers of the SRS for every proof, as when the SRS was kept in
+        // projective form
+        let g_alpha_powers = <G1Placement as KeyPlacement<P>>::srs_powers(&srs, Trapdoor::Alpha)
+            .powers()
+            .iter()
+            .map(|power| power.into_projective())
+            .collect::<Vec<_>>();
+        let h_beta_powers = <G2Placement as KeyPlacement<P>>::srs_powers(&srs, Trapdoor::Beta)
+            .powers()
+            .iter()
+            .map(|power| power.into_projective())
+            .collect::<Vec<_>>();
+        start = Instant::now();
+        for _ in 0..NUM_REPEATED_PROOFS {
+            <P::G1Projective>::batch_normalization_into_affine(&g_alpha_powers);
+            <P::G2Projective>::batch_normalization_into_affine(&h_beta_powers);
+            TIPA::<IP, LMC, RMC, IPC, P, D>::prove(&srs, (&l, &r), (&ck_l, &ck_r, &ck_t)).unwrap();
+        }
+        bench = start.elapsed().as_millis() / NUM_REPEATED_PROOFS as u128;
+        println!("\t repeated proving time with SRS normalization per proof: {} ms per proof", bench);
         start = Instant::now();
         TIPA::<IP, LMC, RMC, IPC, P, D>::verify(&v_srs, &ck_t, (&com_l, &com_r, &com_t), &proof).unwrap();
         bench = start.elapsed().as_millis();
@@ -89,9 +123,8 @@ fn bench_tipa_srs_shift<IP, LMC, RMC, IPC, P, D, R: Rng>(rng: &mut R, len: usize
             RightMessage = RMC::Message,
             Output = IPC::Message,
         >,
-        LMC: DoublyHomomorphicCommitment<Scalar = P::Fr, Key = P::G2Projective> + TIPACompatibleSetup,
-        RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar, Key = P::G1Projective>
-        + TIPACompatibleSetup,
+        LMC: DoublyHomomorphicCommitment<Scalar = P::Fr, Key = P::G2Projective>,
+        RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar, Key = P::G1Projective>,
         IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
         LMC::Message: MulAssign<P::Fr> + Group<ScalarField = P::Fr>,
         RMC::Message: MulAssign<P::Fr> + Group,
@@ -142,29 +175,23 @@ fn bench_tipa_srs_shift<IP, LMC, RMC, IPC, P, D, R: Rng>(rng: &mut R, len: usize
 }
 
 
-fn bench_tipa_ssm<IP, LMC, RMC, IPC, P, D, R: Rng>(rng: &mut R, len: usize)
+fn bench_tipa_ssm<IP, LMC, IPC, P, D, R: Rng>(rng: &mut R, len: usize)
     where
         D: Digest,
         P: PairingEngine,
         IP: InnerProduct<
             LeftMessage = LMC::Message,
-            RightMessage = RMC::Message,
+            RightMessage = LMC::Scalar,
             Output = IPC::Message,
         >,
-        LMC: DoublyHomomorphicCommitment<Scalar = P::Fr, Key = P::G2Projective> + TIPACompatibleSetup,
-        RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar, Key = P::G1Projective, Message = P::Fr>
-        + TIPACompatibleSetup,
+        LMC: DoublyHomomorphicCommitment<Scalar = P::Fr, Key = P::G2Projective>,
         IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
         LMC::Message: MulAssign<P::Fr>,
-        RMC::Message: MulAssign<P::Fr>,
         IPC::Message: MulAssign<P::Fr>,
         IPC::Key: MulAssign<P::Fr>,
         LMC::Output: MulAssign<P::Fr>,
-        RMC::Output: MulAssign<P::Fr>,
         IPC::Output: MulAssign<P::Fr>,
-        IPC::Output: MulAssign<LMC::Scalar>,
         IP::LeftMessage: UniformRand,
-        IP::RightMessage: UniformRand,
 {
     let mut l = Vec::new();
     for _ in 0..len {
@@ -173,29 +200,83 @@ fn bench_tipa_ssm<IP, LMC, RMC, IPC, P, D, R: Rng>(rng: &mut R, len: usize)
     let scalar = <P::Fr>::rand(rng);
     let r = structured_scalar_power(len, &scalar);
 
-    let (srs, ck_t) = TIPA::<IP, LMC, RMC, IPC, P, D>::setup(rng, len).unwrap();
-    let (ck_l, ck_r) = srs.get_commitment_keys();
+    let (srs, ck_t) = TIPAWithSSM::<IP, LMC, IPC, P, D>::setup(rng, len).unwrap();
+    let (ck_l, _) = srs.get_commitment_keys();
     let v_srs = srs.get_verifier_key();
     let com_l = LMC::commit(&ck_l, &l).unwrap();
     let t = vec![IP::inner_product(&l, &r).unwrap()];
     let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
     let mut start = Instant::now();
-    let proof = TIPA::<IP, LMC, RMC, IPC, P, D>::prove_with_structured_scalar_message(
-        &srs, (&l, &r), (&ck_l, &ck_r, &ck_t)).unwrap();
+    let proof = TIPAWithSSM::<IP, LMC, IPC, P, D>::prove_with_structured_scalar_message(
+        &srs, (&l, &r), (&ck_l, &ck_t), &scalar).unwrap();
     let mut bench = start.elapsed().as_millis();
     println!("\t proving time: {} ms", bench);
     start = Instant::now();
-    TIPA::<IP, LMC, RMC, IPC, P, D>::verify_with_structured_scalar_message(
+    TIPAWithSSM::<IP, LMC, IPC, P, D>::verify_with_structured_scalar_message(
         &v_srs, &ck_t, (&com_l, &com_t), &scalar, &proof).unwrap();
     bench = start.elapsed().as_millis();
     println!("\t verification time: {} ms", bench);
 }
 
+fn bench_tipa_sgm<IP, LMC, IPC, P, D, R: Rng>(rng: &mut R, len: usize)
+    where
+        D: Digest,
+        P: PairingEngine,
+        IP: InnerProduct<
+            LeftMessage = LMC::Message,
+            RightMessage = P::G1Projective,
+            Output = IPC::Message,
+        >,
+        LMC: DoublyHomomorphicCommitment<Scalar = P::Fr, Key = P::G2Projective>,
+        IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
+        LMC::Message: MulAssign<P::Fr>,
+        IPC::Message: MulAssign<P::Fr>,
+        IPC::Key: MulAssign<P::Fr>,
+        LMC::Output: MulAssign<P::Fr>,
+        IPC::Output: MulAssign<P::Fr>,
+        IP::LeftMessage: UniformRand,
+{
+    let mut l = Vec::new();
+    for _ in 0..len {
+        l.push(<IP::LeftMessage>::rand(rng));
+    }
+
+    let (srs, ck_t) = TIPAWithSGM::<IP, LMC, IPC, P, D>::setup(rng, len).unwrap();
+    let (ck_l, r) = srs.get_commitment_keys();
+    let v_srs = srs.get_verifier_key();
+    let com_l = LMC::commit(&ck_l, &l).unwrap();
+    let t = vec![IP::inner_product(&l, &r).unwrap()];
+    let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+    let mut start = Instant::now();
+    let proof = TIPAWithSGM::<IP, LMC, IPC, P, D>::prove_with_structured_generators_message(
+        &srs, (&l, &r), (&ck_l, &ck_t)).unwrap();
+    let mut bench = start.elapsed().as_millis();
+    println!("\t proving time: {} ms", bench);
+    start = Instant::now();
+    TIPAWithSGM::<IP, LMC, IPC, P, D>::verify_with_structured_generators_message(
+        &v_srs, &ck_t, (&com_l, &com_t), &proof).unwrap();
+    bench = start.elapsed().as_millis();
+    println!("\t verification time: {} ms", bench);
+}
+
+fn bench_pairing_tipa<P: PairingEngine, R: Rng>(rng: &mut R, len: usize) {
+    bench_tipa::<
+        PairingInnerProduct<P>,
+        AFGHOCommitmentG1<P>,
+        AFGHOCommitmentG2<P>,
+        IdentityCommitment<ExtensionFieldElement<P>, P::Fr>,
+        P,
+        Blake2b,
+        R,
+    >(rng, len);
+}
+
 fn main() {
     const LEN: usize = 16;
     type GC1 = AFGHOCommitmentG1<Bls12_381>;
     type GC2 = AFGHOCommitmentG2<Bls12_381>;
     type SC1 = PedersenCommitment<<Bls12_381 as PairingEngine>::G1Projective>;
+    type SC2 = PedersenCommitment<<Bls12_381 as PairingEngine>::G2Projective>;
     let mut rng = StdRng::seed_from_u64(0u64);
 
     println!("Benchmarking TIPA with vector length: {}", LEN);
@@ -222,7 +303,18 @@ fn main() {
         StdRng,
     >(&mut rng, LEN);
 
-    println!("3) Pairing inner product with SRS shift...");
+    println!("3) Reverse multiexponentiation G2 inner product...");
+    bench_tipa::<
+        ReverseMultiexponentiationInnerProduct<<Bls12_381 as PairingEngine>::G2Projective>,
+        SC2,
+        GC2,
+        IdentityCommitment<<Bls12_381 as PairingEngine>::G2Projective, <Bls12_381 as PairingEngine>::Fr>,
+        Bls12_381,
+        Blake2b,
+        StdRng,
+    >(&mut rng, LEN);
+
+    println!("4) Pairing inner product with SRS shift...");
     bench_tipa_srs_shift::<
         PairingInnerProduct<Bls12_381>,
         GC1,
@@ -233,15 +325,33 @@ fn main() {
         StdRng,
     >(&mut rng, LEN);
 
-    println!("4) Multiexponentiation G1 inner product with structured scalar message...");
+    println!("5) Multiexponentiation G1 inner product with structured scalar message...");
     bench_tipa_ssm::<
         MultiexponentiationInnerProduct<<Bls12_381 as PairingEngine>::G1Projective>,
         GC1,
-        SC1,
         IdentityCommitment<<Bls12_381 as PairingEngine>::G1Projective, <Bls12_381 as PairingEngine>::Fr>,
         Bls12_381,
         Blake2b,
         StdRng,
     >(&mut rng, LEN);
 
+    println!("6) Reverse multiexponentiation G1 inner product with structured generators message...");
+    bench_tipa_sgm::<
+        ReverseMultiexponentiationInnerProduct<<Bls12_381 as PairingEngine>::G1Projective>,
+        SC2,
+        IdentityCommitment<<Bls12_381 as PairingEngine>::G1Projective, <Bls12_381 as PairingEngine>::Fr>,
+        Bls12_381,
+        Blake2b,
+        StdRng,
+    >(&mut rng, LEN);
+
+    println!("7) Pairing inner product on other curves...");
+    println!("BLS12-377:");
+    bench_pairing_tipa::<Bls12_377, StdRng>(&mut rng, LEN);
+    println!("BN254:");
+    bench_pairing_tipa::<Bn254, StdRng>(&mut rng, LEN);
+    println!("MNT4-298:");
+    bench_pairing_tipa::<MNT4_298, StdRng>(&mut rng, LEN);
+    println!("MNT6-298:");
+    bench_pairing_tipa::<MNT6_298, StdRng>(&mut rng, LEN);
 }
@@ -1,8 +1,11 @@
 use algebra::curves::PairingEngine;
+use digest::Digest;
 use rand::Rng;
 use std::marker::PhantomData;
 
-use crate::{random_generators, DoublyHomomorphicCommitment, Error};
+use crate::{
+    hash_to_generators, random_generators, DoublyHomomorphicCommitment, Error, TransparentSetup,
+};
 
 use inner_products::{ExtensionFieldElement, InnerProduct, PairingInnerProduct};
 
@@ -47,47 +50,83 @@ impl<P: PairingEngine> DoublyHomomorphicCommitment for AFGHOCommitmentG2<P> {
     }
 }
 
+impl<P: PairingEngine> TransparentSetup for AFGHOCommitmentG1<P> {
+    fn setup_from_label<D: Digest>(label: &[u8], size: usize) -> Result<Vec<Self::Key>, Error> {
+        hash_to_generators::<D, P::G2Projective>(label, size)
+    }
+}
+
+impl<P: PairingEngine> TransparentSetup for AFGHOCommitmentG2<P> {
+    fn setup_from_label<D: Digest>(label: &[u8], size: usize) -> Result<Vec<Self::Key>, Error> {
+        hash_to_generators::<D, P::G1Projective>(label, size)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use algebra::{bls12_381::Bls12_381, UniformRand};
+    use algebra::UniformRand;
+    use blake2::Blake2b;
     use rand::{rngs::StdRng, SeedableRng};
 
-    type C1 = AFGHOCommitmentG1<Bls12_381>;
-    type C2 = AFGHOCommitmentG2<Bls12_381>;
-    const TEST_SIZE: usize = 8;
-
-    #[test]
-    fn afgho_g1_test() {
-        let mut rng = StdRng::seed_from_u64(0u64);
-        let commit_keys = C1::setup(&mut rng, TEST_SIZE).unwrap();
-        let mut message = Vec::new();
-        let mut wrong_message = Vec::new();
-        for _ in 0..TEST_SIZE {
-            message.push(<Bls12_381 as PairingEngine>::G1Projective::rand(&mut rng));
-            wrong_message.push(<Bls12_381 as PairingEngine>::G1Projective::rand(&mut rng));
+    pairing_engine_tests! {
+        type C1 = AFGHOCommitmentG1<E>;
+        type C2 = AFGHOCommitmentG2<E>;
+        const TEST_SIZE: usize = 8;
+
+        #[test]
+        fn afgho_g1_test() {
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let commit_keys = C1::setup(&mut rng, TEST_SIZE).unwrap();
+            let mut message = Vec::new();
+            let mut wrong_message = Vec::new();
+            for _ in 0..TEST_SIZE {
+                message.push(<E as PairingEngine>::G1Projective::rand(&mut rng));
+                wrong_message.push(<E as PairingEngine>::G1Projective::rand(&mut rng));
+            }
+            let com = C1::commit(&commit_keys, &message).unwrap();
+            assert!(C1::verify(&commit_keys, &message, &com).unwrap());
+            assert!(!C1::verify(&commit_keys, &wrong_message, &com).unwrap());
+            message.push(<E as PairingEngine>::G1Projective::rand(&mut rng));
+            assert!(C1::verify(&commit_keys, &message, &com).is_err());
+        }
+
+        #[test]
+        fn afgho_g2_test() {
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let commit_keys = C2::setup(&mut rng, TEST_SIZE).unwrap();
+            let mut message = Vec::new();
+            let mut wrong_message = Vec::new();
+            for _ in 0..TEST_SIZE {
+                message.push(<E as PairingEngine>::G2Projective::rand(&mut rng));
+                wrong_message.push(<E as PairingEngine>::G2Projective::rand(&mut rng));
+            }
+            let com = C2::commit(&commit_keys, &message).unwrap();
+            assert!(C2::verify(&commit_keys, &message, &com).unwrap());
+            assert!(!C2::verify(&commit_keys, &wrong_message, &com).unwrap());
+            message.push(<E as PairingEngine>::G2Projective::rand(&mut rng));
+            assert!(C2::verify(&commit_keys, &message, &com).is_err());
         }
-        let com = C1::commit(&commit_keys, &message).unwrap();
-        assert!(C1::verify(&commit_keys, &message, &com).unwrap());
-        assert!(!C1::verify(&commit_keys, &wrong_message, &com).unwrap());
-        message.push(<Bls12_381 as PairingEngine>::G1Projective::rand(&mut rng));
-        assert!(C1::verify(&commit_keys, &message, &com).is_err());
-    }
 
-    #[test]
-    fn afgho_g2_test() {
-        let mut rng = StdRng::seed_from_u64(0u64);
-        let commit_keys = C2::setup(&mut rng, TEST_SIZE).unwrap();
-        let mut message = Vec::new();
-        let mut wrong_message = Vec::new();
-        for _ in 0..TEST_SIZE {
-            message.push(<Bls12_381 as PairingEngine>::G2Projective::rand(&mut rng));
-            wrong_message.push(<Bls12_381 as PairingEngine>::G2Projective::rand(&mut rng));
+        #[test]
+        fn afgho_transparent_setup_test() {
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let commit_keys_1 = C1::setup_from_label::<Blake2b>(b"afgho", TEST_SIZE).unwrap();
+            let commit_keys_2 = C2::setup_from_label::<Blake2b>(b"afgho", TEST_SIZE).unwrap();
+            assert_eq!(
+                commit_keys_1,
+                C1::setup_from_label::<Blake2b>(b"afgho", TEST_SIZE).unwrap()
+            );
+            let mut message_1 = Vec::new();
+            let mut message_2 = Vec::new();
+            for _ in 0..TEST_SIZE {
+                message_1.push(<E as PairingEngine>::G1Projective::rand(&mut rng));
+                message_2.push(<E as PairingEngine>::G2Projective::rand(&mut rng));
+            }
+            let com_1 = C1::commit(&commit_keys_1, &message_1).unwrap();
+            let com_2 = C2::commit(&commit_keys_2, &message_2).unwrap();
+            assert!(C1::verify(&commit_keys_1, &message_1, &com_1).unwrap());
+            assert!(C2::verify(&commit_keys_2, &message_2, &com_2).unwrap());
         }
-        let com = C2::commit(&commit_keys, &message).unwrap();
-        assert!(C2::verify(&commit_keys, &message, &com).unwrap());
-        assert!(!C2::verify(&commit_keys, &wrong_message, &com).unwrap());
-        message.push(<Bls12_381 as PairingEngine>::G2Projective::rand(&mut rng));
-        assert!(C2::verify(&commit_keys, &message, &com).is_err());
     }
 }
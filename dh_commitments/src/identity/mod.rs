@@ -1,4 +1,5 @@
 use algebra::{bytes::ToBytes, fields::PrimeField};
+use digest::Digest;
 use rand::Rng;
 use std::{
     io::{Result as IoResult, Write},
@@ -6,7 +7,7 @@ use std::{
     ops::{Add, MulAssign},
 };
 
-use crate::{DoublyHomomorphicCommitment, Error};
+use crate::{DoublyHomomorphicCommitment, Error, TransparentSetup};
 
 #[derive(Clone)]
 pub struct IdentityCommitment<T, F: PrimeField> {
@@ -81,4 +82,22 @@ where
     fn commit(_k: &[Self::Key], m: &[Self::Message]) -> Result<Self::Output, Error> {
         Ok(IdentityOutput(m.to_vec()))
     }
+
+    fn is_identity() -> bool {
+        true
+    }
+
+    fn reveal(com: &Self::Output) -> Option<Vec<Self::Message>> {
+        Some(com.0.clone())
+    }
+}
+
+impl<T, F> TransparentSetup for IdentityCommitment<T, F>
+where
+    T: ToBytes + Clone + Default + Eq + Add<T, Output = T> + MulAssign<F>,
+    F: PrimeField,
+{
+    fn setup_from_label<D: Digest>(_label: &[u8], size: usize) -> Result<Vec<Self::Key>, Error> {
+        Ok(vec![HomomorphicPlaceholderValue {}; size])
+    }
 }
@@ -1,9 +1,13 @@
 use algebra::{
     bytes::ToBytes,
+    curves::{AffineCurve, ProjectiveCurve},
     fields::PrimeField,
     //    serialize::CanonicalSerialize,
     groups::Group,
+    to_bytes,
 };
+use digest::Digest;
+use num_traits::identities::Zero;
 use rand::Rng;
 use std::{
     cmp::Eq,
@@ -11,6 +15,44 @@ use std::{
     ops::{Add, MulAssign},
 };
 
+// Test suites are instantiated for each pairing engine, which they refer to as `E`. Exported for the
+// tests of dependent crates, which need `algebra` with the curve features as a dependency
+#[cfg(any(test, feature = "test-utils"))]
+#[macro_export]
+macro_rules! pairing_engine_tests {
+    ($($tests:item)*) => {
+        mod bls12_377 {
+            use super::*;
+            type E = algebra::bls12_377::Bls12_377;
+            $($tests)*
+        }
+
+        mod bls12_381 {
+            use super::*;
+            type E = algebra::bls12_381::Bls12_381;
+            $($tests)*
+        }
+
+        mod bn254 {
+            use super::*;
+            type E = algebra::bn254::Bn254;
+            $($tests)*
+        }
+
+        mod mnt4_298 {
+            use super::*;
+            type E = algebra::mnt4_298::MNT4_298;
+            $($tests)*
+        }
+
+        mod mnt6_298 {
+            use super::*;
+            type E = algebra::mnt6_298::MNT6_298;
+            $($tests)*
+        }
+    };
+}
+
 pub mod afgho16;
 pub mod identity;
 pub mod pedersen;
@@ -48,6 +90,22 @@ pub trait DoublyHomomorphicCommitment: Clone {
     fn verify(k: &[Self::Key], m: &[Self::Message], com: &Self::Output) -> Result<bool, Error> {
         Ok(Self::commit(k, m)? == *com)
     }
+
+    // Identity commitments reveal the committed message, so arguments can treat the message as
+    // public and let the verifier work with it directly instead of with commitments
+    fn is_identity() -> bool {
+        false
+    }
+
+    fn reveal(_com: &Self::Output) -> Option<Vec<Self::Message>> {
+        None
+    }
+}
+
+// Commitment schemes whose keys can be derived deterministically from a public label, so that no
+// party learns a trapdoor for them and verifiers can rederive the keys themselves
+pub trait TransparentSetup: DoublyHomomorphicCommitment {
+    fn setup_from_label<D: Digest>(label: &[u8], size: usize) -> Result<Vec<Self::Key>, Error>;
 }
 
 // Helpers for generator commitment keys used by Pedersen and AFGHO16
@@ -55,3 +113,101 @@ pub trait DoublyHomomorphicCommitment: Clone {
 pub fn random_generators<R: Rng, G: Group>(rng: &mut R, num: usize) -> Vec<G> {
     (0..num).map(|_| G::rand(rng)).collect()
 }
+
+const HASH_TO_CURVE_DOMAIN: &[u8] = b"dh-commitments-hash-to-curve";
+
+// Generators are derived by try-and-increment: the label, index and a counter are hashed to candidate
+// bytes until they decode to a curve point, which is mapped to the prime order subgroup by clearing
+// the cofactor. The discrete logarithms of the generators are unknown to everyone, including whoever
+// derives them. Hashes are domain separated by the curve (through its subgroup generator) and label.
+pub fn hash_to_generators<D: Digest, G: ProjectiveCurve>(
+    label: &[u8],
+    num: usize,
+) -> Result<Vec<G>, Error> {
+    let curve = to_bytes![G::Affine::prime_subgroup_generator()]?;
+    // Enough bytes for the coordinates of a point, of which the x-coordinate and flags are decoded
+    let num_bytes = curve.len();
+    let mut prefix = Vec::new();
+    prefix.extend_from_slice(HASH_TO_CURVE_DOMAIN);
+    prefix.extend_from_slice(&(curve.len() as u64).to_be_bytes()[..]);
+    prefix.extend_from_slice(&curve);
+    prefix.extend_from_slice(&(label.len() as u64).to_be_bytes()[..]);
+    prefix.extend_from_slice(label);
+
+    Ok((0..num)
+        .map(|i| {
+            let mut counter_nonce: u64 = 0;
+            loop {
+                let mut hash_input = prefix.clone();
+                hash_input.extend_from_slice(&(i as u64).to_be_bytes()[..]);
+                hash_input.extend_from_slice(&counter_nonce.to_be_bytes()[..]);
+                let bytes = expand_hash::<D>(&hash_input, num_bytes);
+                if let Some(point) = G::Affine::from_random_bytes(&bytes) {
+                    let generator = point.mul_by_cofactor_to_projective();
+                    if !generator.is_zero() {
+                        break generator;
+                    }
+                }
+                counter_nonce += 1;
+            }
+        })
+        .collect())
+}
+
+// Expands the hash of the input to `num_bytes` bytes by hashing it with a block counter
+pub fn expand_hash<D: Digest>(input: &[u8], num_bytes: usize) -> Vec<u8> {
+    let input_hash = D::digest(input);
+    let mut bytes = Vec::with_capacity(num_bytes);
+    let mut block: u32 = 0;
+    while bytes.len() < num_bytes {
+        let mut block_input = Vec::new();
+        block_input.extend_from_slice(&input_hash);
+        block_input.extend_from_slice(&block.to_be_bytes()[..]);
+        bytes.extend_from_slice(&D::digest(&block_input));
+        block += 1;
+    }
+    bytes.truncate(num_bytes);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::curves::PairingEngine;
+    use blake2::{Blake2b, Blake2s};
+
+    pairing_engine_tests! {
+        type G1Projective = <E as PairingEngine>::G1Projective;
+        type G2Projective = <E as PairingEngine>::G2Projective;
+
+        const TEST_SIZE: usize = 8;
+
+        #[test]
+        fn hash_to_generators_test() {
+            let g1 = hash_to_generators::<Blake2b, G1Projective>(b"label", TEST_SIZE).unwrap();
+            let g2 = hash_to_generators::<Blake2b, G2Projective>(b"label", TEST_SIZE).unwrap();
+            assert_eq!(g1.len(), TEST_SIZE);
+            assert_eq!(g2.len(), TEST_SIZE);
+            for (i, (a, b)) in g1.iter().zip(&g2).enumerate() {
+                assert!(!a.is_zero() && !b.is_zero());
+                assert!(a.into_affine().is_in_correct_subgroup_assuming_on_curve());
+                assert!(b.into_affine().is_in_correct_subgroup_assuming_on_curve());
+                assert!(g1[..i].iter().all(|other| other != a));
+            }
+
+            // Deterministic in the label, and separated by label and hash
+            assert_eq!(
+                g1,
+                hash_to_generators::<Blake2b, G1Projective>(b"label", TEST_SIZE).unwrap()
+            );
+            assert_ne!(
+                g1[0],
+                hash_to_generators::<Blake2b, G1Projective>(b"other label", 1).unwrap()[0]
+            );
+            assert_ne!(
+                g1[0],
+                hash_to_generators::<Blake2s, G1Projective>(b"label", 1).unwrap()[0]
+            );
+        }
+    }
+}
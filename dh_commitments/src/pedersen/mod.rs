@@ -1,10 +1,13 @@
 use algebra::{
     curves::ProjectiveCurve,
 };
+use digest::Digest;
 use rand::Rng;
 use std::marker::PhantomData;
 
-use crate::{random_generators, DoublyHomomorphicCommitment, Error};
+use crate::{
+    hash_to_generators, random_generators, DoublyHomomorphicCommitment, Error, TransparentSetup,
+};
 
 use inner_products::{InnerProduct, MultiexponentiationInnerProduct};
 
@@ -28,10 +31,17 @@ impl<G: ProjectiveCurve> DoublyHomomorphicCommitment for PedersenCommitment<G> {
     }
 }
 
+impl<G: ProjectiveCurve> TransparentSetup for PedersenCommitment<G> {
+    fn setup_from_label<D: Digest>(label: &[u8], size: usize) -> Result<Vec<Self::Key>, Error> {
+        hash_to_generators::<D, G>(label, size)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use algebra::{ed_on_bls12_381::EdwardsProjective as JubJub, UniformRand};
+    use blake2::Blake2b;
     use rand::{rngs::StdRng, SeedableRng};
 
     type C = PedersenCommitment<JubJub>;
@@ -53,4 +63,20 @@ mod tests {
         message.push(<JubJub as ProjectiveCurve>::ScalarField::rand(&mut rng));
         assert!(C::verify(&commit_keys, &message, &com).is_err());
     }
+
+    #[test]
+    fn pedersen_transparent_setup_test() {
+        let mut rng = StdRng::seed_from_u64(0u64);
+        let commit_keys = C::setup_from_label::<Blake2b>(b"pedersen", TEST_SIZE).unwrap();
+        assert_eq!(
+            commit_keys,
+            C::setup_from_label::<Blake2b>(b"pedersen", TEST_SIZE).unwrap()
+        );
+        let mut message = Vec::new();
+        for _ in 0..TEST_SIZE {
+            message.push(<JubJub as ProjectiveCurve>::ScalarField::rand(&mut rng));
+        }
+        let com = C::commit(&commit_keys, &message).unwrap();
+        assert!(C::verify(&commit_keys, &message, &com).unwrap());
+    }
 }
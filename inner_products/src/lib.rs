@@ -104,6 +104,31 @@ impl<G: ProjectiveCurve> InnerProduct for MultiexponentiationInnerProduct<G>
     }
 }
 
+// Multiexponentiation with the scalars on the left, allowing group element messages on the right
+#[derive(Copy, Clone)]
+pub struct ReverseMultiexponentiationInnerProduct<G: ProjectiveCurve> {
+    _projective: PhantomData<G>,
+}
+
+impl<G: ProjectiveCurve> InnerProduct for ReverseMultiexponentiationInnerProduct<G> {
+    type LeftMessage = G::ScalarField;
+    type RightMessage = G;
+    type Output = G;
+
+    fn inner_product(
+        left: &[Self::LeftMessage],
+        right: &[Self::RightMessage],
+    ) -> Result<Self::Output, Error> {
+        if left.len() != right.len() {
+            return Err(Box::new(InnerProductError::MessageLengthInvalid(
+                left.len(),
+                right.len(),
+            )));
+        };
+        MultiexponentiationInnerProduct::<G>::inner_product(right, left)
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct ScalarInnerProduct<F: Field> {
     _field: PhantomData<F>,
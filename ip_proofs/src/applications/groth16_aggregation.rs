@@ -16,15 +16,15 @@ use num_traits::identities::One;
 use dh_commitments::{
     afgho16::{AFGHOCommitmentG1, AFGHOCommitmentG2},
     identity::{HomomorphicPlaceholderValue, IdentityCommitment, IdentityOutput},
-    pedersen::PedersenCommitment,
 };
 use inner_products::{
     ExtensionFieldElement, InnerProduct, MultiexponentiationInnerProduct, PairingInnerProduct,
     ScalarInnerProduct,
 };
 use crate::{
+    fiat_shamir::hash_to_challenge,
     tipa::{
-        structured_scalar_message::{structured_scalar_power, TIPAWithSSMProof},
+        structured_scalar_message::{structured_scalar_power, TIPAWithSSM, TIPAWithSSMProof},
         TIPAProof, VerifierSRS, SRS, TIPA,
     },
     Error,
@@ -48,10 +48,9 @@ type PairingInnerProductABProof<P, D> = TIPAProof<
     D,
 >;
 
-type MultiExpInnerProductC<P, D> = TIPA<
+type MultiExpInnerProductC<P, D> = TIPAWithSSM<
     MultiexponentiationInnerProduct<<P as PairingEngine>::G1Projective>,
     AFGHOCommitmentG1<P>,
-    PedersenCommitment<<P as PairingEngine>::G1Projective>,
     IdentityCommitment<<P as PairingEngine>::G1Projective, <P as PairingEngine>::Fr>,
     P,
     D,
@@ -60,7 +59,6 @@ type MultiExpInnerProductC<P, D> = TIPA<
 type MultiExpInnerProductCProof<P, D> = TIPAWithSSMProof<
     MultiexponentiationInnerProduct<<P as PairingEngine>::G1Projective>,
     AFGHOCommitmentG1<P>,
-    PedersenCommitment<<P as PairingEngine>::G1Projective>,
     IdentityCommitment<<P as PairingEngine>::G1Projective, <P as PairingEngine>::Fr>,
     P,
     D,
@@ -113,17 +111,8 @@ where
     let com_c = PairingInnerProduct::<P>::inner_product(&c, &ck_1)?;
 
     // Random linear combination of proofs
-    let mut counter_nonce: usize = 0;
-    let r = loop {
-        let mut hash_input = Vec::new();
-        hash_input.extend_from_slice(&counter_nonce.to_be_bytes()[..]);
-        //TODO: Should use CanonicalSerialize instead of ToBytes
-        hash_input.extend_from_slice(&to_bytes![com_a, com_b, com_c]?);
-        if let Some(r) = <P::Fr>::from_random_bytes(&D::digest(&hash_input)) {
-            break r;
-        };
-        counter_nonce += 1;
-    };
+    //TODO: Should use CanonicalSerialize instead of ToBytes
+    let r = hash_to_challenge::<P::Fr, D>(&to_bytes![com_a, com_b, com_c]?);
 
     let r_vec = structured_scalar_power(proofs.len(), &r);
     let a_r = a
@@ -155,7 +144,8 @@ where
     let tipa_proof_c = MultiExpInnerProductC::<P, D>::prove_with_structured_scalar_message(
         &ip_srs,
         (&c, &r_vec),
-        (&ck_1, &ck_2, &HomomorphicPlaceholderValue),
+        (&ck_1, &HomomorphicPlaceholderValue),
+        &r,
     )?;
 
     Ok(AggregateProof {
@@ -180,17 +170,8 @@ where
     D: Digest,
 {
     // Random linear combination of proofs
-    let mut counter_nonce: usize = 0;
-    let r = loop {
-        let mut hash_input = Vec::new();
-        hash_input.extend_from_slice(&counter_nonce.to_be_bytes()[..]);
-        //TODO: Should use CanonicalSerialize instead of ToBytes
-        hash_input.extend_from_slice(&to_bytes![proof.com_a, proof.com_b, proof.com_c]?);
-        if let Some(r) = <P::Fr>::from_random_bytes(&D::digest(&hash_input)) {
-            break r;
-        };
-        counter_nonce += 1;
-    };
+    //TODO: Should use CanonicalSerialize instead of ToBytes
+    let r = hash_to_challenge::<P::Fr, D>(&to_bytes![proof.com_a, proof.com_b, proof.com_c]?);
 
     // Check TIPA proofs
     let tipa_proof_ab_valid = PairingInnerProductAB::<P, D>::verify_with_srs_shift(
@@ -239,3 +220,102 @@ where
 
     Ok(tipa_proof_ab_valid && tipa_proof_c_valid && ppe_valid)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::{fields::PrimeField, UniformRand};
+    use blake2::Blake2b;
+    use groth16::{create_random_proof, generate_random_parameters};
+    use r1cs_core::{ConstraintSynthesizer, ConstraintSystem, SynthesisError};
+    use r1cs_std::{
+        alloc::AllocGadget,
+        eq::EqGadget,
+        fields::{fp::FpGadget, FieldGadget},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[derive(Clone)]
+    struct TestCircuit<F: PrimeField> {
+        public_inputs: Vec<F>,
+        witness_input: F,
+        public_sum: F,
+    }
+
+    impl<F: PrimeField> ConstraintSynthesizer<F> for TestCircuit<F> {
+        fn generate_constraints<CS: ConstraintSystem<F>>(
+            self,
+            cs: &mut CS,
+        ) -> Result<(), SynthesisError> {
+            let input_variables =
+                Vec::<FpGadget<F>>::alloc_input(&mut cs.ns(|| "public_inputs"), || {
+                    Ok(self.public_inputs.clone())
+                })?;
+            let sum = <FpGadget<F>>::alloc_input(&mut cs.ns(|| "sum_input"), || Ok(&self.public_sum))?;
+            let witness = <FpGadget<F>>::alloc(&mut cs.ns(|| "witness"), || Ok(&self.witness_input))?;
+
+            let mut computed_sum = witness;
+            for (i, x) in input_variables.iter().enumerate() {
+                computed_sum = computed_sum.add(&mut cs.ns(|| format!("comp_sum_{}", i)), x)?;
+            }
+
+            sum.enforce_equal(&mut cs.ns(|| "check_sum"), &computed_sum)?;
+
+            Ok(())
+        }
+    }
+
+    pairing_engine_tests! {
+        const NUM_PUBLIC_INPUTS: usize = 4;
+        const NUM_PROOFS_TO_AGGREGATE: usize = 8;
+
+        #[test]
+        fn aggregate_proofs_test() {
+            type Fr = <E as PairingEngine>::Fr;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let test_circuit = TestCircuit::<Fr> {
+                public_inputs: vec![Default::default(); NUM_PUBLIC_INPUTS],
+                public_sum: Default::default(),
+                witness_input: Default::default(),
+            };
+            let parameters = generate_random_parameters::<E, _, _>(test_circuit, &mut rng).unwrap();
+            let srs =
+                setup_inner_product::<E, Blake2b, _>(&mut rng, NUM_PROOFS_TO_AGGREGATE).unwrap();
+
+            let mut proofs = Vec::new();
+            let mut statements = Vec::new();
+            for _ in 0..NUM_PROOFS_TO_AGGREGATE {
+                let public_inputs = (0..NUM_PUBLIC_INPUTS)
+                    .map(|_| Fr::rand(&mut rng))
+                    .collect::<Vec<_>>();
+                let w = Fr::rand(&mut rng);
+                let sum: Fr = w.clone() + &public_inputs.iter().sum();
+                let mut statement = public_inputs.clone();
+                statement.push(sum.clone());
+                let circuit = TestCircuit {
+                    public_inputs,
+                    public_sum: sum,
+                    witness_input: w,
+                };
+                proofs.push(create_random_proof(circuit, &parameters, &mut rng).unwrap());
+                statements.push(statement);
+            }
+
+            let aggregate_proof = aggregate_proofs::<E, Blake2b>(&srs, &proofs).unwrap();
+            let v_srs = srs.get_verifier_key();
+            assert!(
+                verify_aggregate_proof(&v_srs, &parameters.vk, &statements, &aggregate_proof)
+                    .unwrap()
+            );
+
+            // Wrong public input
+            let mut wrong_statements = statements.clone();
+            wrong_statements[0][0] = Fr::rand(&mut rng);
+            assert!(
+                !verify_aggregate_proof(&v_srs, &parameters.vk, &wrong_statements, &aggregate_proof)
+                    .unwrap()
+            );
+        }
+    }
+}
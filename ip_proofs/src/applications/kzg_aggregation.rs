@@ -0,0 +1,351 @@
+use algebra::{bytes::ToBytes, curves::PairingEngine, groups::Group, to_bytes};
+use num_traits::identities::One;
+
+use rand::Rng;
+use digest::Digest;
+
+use dh_commitments::{
+    afgho16::AFGHOCommitmentG1,
+    identity::{HomomorphicPlaceholderValue, IdentityCommitment, IdentityOutput},
+    DoublyHomomorphicCommitment,
+};
+use inner_products::{
+    ExtensionFieldElement, InnerProduct, MultiexponentiationInnerProduct, ScalarInnerProduct,
+};
+use crate::{
+    ck_proof::{GIPAWithCKP, GIPAWithCKPProof, IdentityKeyProof},
+    fiat_shamir::hash_to_challenge,
+    tipa::{
+        structured_scalar_message::{structured_scalar_power, TIPAWithSSM, TIPAWithSSMProof},
+        BetaPowers, G2Placement, KZGKeyProof, VerifierSRS, SRS,
+    },
+    Error, InnerProductArgumentError,
+};
+
+type MultiExpInnerProduct<P, D> = TIPAWithSSM<
+    MultiexponentiationInnerProduct<<P as PairingEngine>::G1Projective>,
+    AFGHOCommitmentG1<P>,
+    IdentityCommitment<<P as PairingEngine>::G1Projective, <P as PairingEngine>::Fr>,
+    P,
+    D,
+>;
+
+type MultiExpInnerProductProof<P, D> = TIPAWithSSMProof<
+    MultiexponentiationInnerProduct<<P as PairingEngine>::G1Projective>,
+    AFGHOCommitmentG1<P>,
+    IdentityCommitment<<P as PairingEngine>::G1Projective, <P as PairingEngine>::Fr>,
+    P,
+    D,
+>;
+
+// Multiexponentiation of the committed opening proofs with public scalars, which the verifier folds
+// itself; the left commitment key is proven with a KZG opening under the same SRS as for TIPA
+type PublicMultiExpInnerProduct<P, D> = GIPAWithCKP<
+    MultiexponentiationInnerProduct<<P as PairingEngine>::G1Projective>,
+    AFGHOCommitmentG1<P>,
+    IdentityCommitment<<P as PairingEngine>::Fr, <P as PairingEngine>::Fr>,
+    IdentityCommitment<<P as PairingEngine>::G1Projective, <P as PairingEngine>::Fr>,
+    KZGKeyProof<P, G2Placement, BetaPowers>,
+    IdentityKeyProof<<P as PairingEngine>::Fr>,
+    D,
+>;
+
+type PublicMultiExpInnerProductProof<P, D> = GIPAWithCKPProof<
+    MultiexponentiationInnerProduct<<P as PairingEngine>::G1Projective>,
+    AFGHOCommitmentG1<P>,
+    IdentityCommitment<<P as PairingEngine>::Fr, <P as PairingEngine>::Fr>,
+    IdentityCommitment<<P as PairingEngine>::G1Projective, <P as PairingEngine>::Fr>,
+    KZGKeyProof<P, G2Placement, BetaPowers>,
+    IdentityKeyProof<<P as PairingEngine>::Fr>,
+    D,
+>;
+
+// Verifying key of the KZG commitments whose openings are aggregated, with h_tau = h^tau for the
+// KZG trapdoor tau
+#[derive(Clone)]
+pub struct KZGVerifierKey<P: PairingEngine> {
+    pub g: P::G1Projective,
+    pub h: P::G2Projective,
+    pub h_tau: P::G2Projective,
+}
+
+// Claim that the polynomial committed to by com evaluates to eval at point
+#[derive(Clone)]
+pub struct KZGOpening<P: PairingEngine> {
+    pub com: P::G1Projective,
+    pub point: P::Fr,
+    pub eval: P::Fr,
+}
+
+// Each opening proof pi_i satisfies e(com_i - g^{eval_i} + pi_i^{point_i}, h) = e(pi_i, h_tau). For
+// a random r, the openings are checked at once from the aggregates sum_i r^i pi_i and
+// sum_i r^i point_i pi_i of the committed proofs, each proven with an inner product argument.
+pub struct AggregateOpeningProof<P: PairingEngine, D: Digest> {
+    com_proofs: ExtensionFieldElement<P>,
+    agg_proof: P::G1Projective,
+    agg_point_proof: P::G1Projective,
+    tipa_proof: MultiExpInnerProductProof<P, D>,
+    gipa_point_proof: PublicMultiExpInnerProductProof<P, D>,
+}
+
+pub fn setup_inner_product<P, D, R: Rng>(rng: &mut R, size: usize) -> Result<SRS<P>, Error>
+    where
+    P: PairingEngine,
+    D: Digest,
+{
+    let (srs, _) = MultiExpInnerProduct::<P, D>::setup(rng, size)?;
+    Ok(srs)
+}
+
+// The inner product SRS must be set up for the number of openings
+pub fn aggregate_openings<P, D>(
+    ip_srs: &SRS<P>,
+    kzg_vk: &KZGVerifierKey<P>,
+    openings: &[KZGOpening<P>],
+    proofs: &[P::G1Projective],
+) -> Result<AggregateOpeningProof<P, D>, Error>
+where
+    P: PairingEngine,
+    D: Digest,
+{
+    if openings.len() != proofs.len() {
+        return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+            openings.len(),
+            proofs.len(),
+        )));
+    }
+
+    let (ck_1, _) = ip_srs.get_commitment_keys();
+    let com_proofs = AFGHOCommitmentG1::<P>::commit(ck_1, proofs)?;
+
+    // Random linear combination of openings
+    let r = opening_challenge::<P, D>(kzg_vk, openings, &com_proofs)?;
+    let r_vec = structured_scalar_power(openings.len(), &r);
+    let point_r_vec = point_scalars(openings, &r_vec);
+
+    let agg_proof =
+        MultiexponentiationInnerProduct::<P::G1Projective>::inner_product(proofs, &r_vec)?;
+    let agg_point_proof =
+        MultiexponentiationInnerProduct::<P::G1Projective>::inner_product(proofs, &point_r_vec)?;
+
+    let tipa_proof = MultiExpInnerProduct::<P, D>::prove_with_structured_scalar_message(
+        ip_srs,
+        (proofs, &r_vec),
+        (ck_1, &HomomorphicPlaceholderValue),
+        &r,
+    )?;
+    let gipa_point_proof = PublicMultiExpInnerProduct::<P, D>::prove_with_context(
+        (proofs, &point_r_vec),
+        (
+            ck_1,
+            &vec![HomomorphicPlaceholderValue; openings.len()],
+            &HomomorphicPlaceholderValue,
+        ),
+        (ip_srs, &(), &()),
+        &<P::Fr>::one(),
+        &gipa_context::<P, D>(&ip_srs.get_verifier_key(), &r)?,
+    )?;
+
+    Ok(AggregateOpeningProof {
+        com_proofs,
+        agg_proof,
+        agg_point_proof,
+        tipa_proof,
+        gipa_point_proof,
+    })
+}
+
+pub fn verify_aggregate_openings<P, D>(
+    ip_verifier_srs: &VerifierSRS<P>,
+    kzg_vk: &KZGVerifierKey<P>,
+    openings: &[KZGOpening<P>],
+    proof: &AggregateOpeningProof<P, D>,
+) -> Result<bool, Error>
+where
+    P: PairingEngine,
+    D: Digest,
+{
+    // The proofs must be for the number of openings
+    let num_rounds = openings.len().trailing_zeros() as usize;
+    if !openings.len().is_power_of_two()
+        || proof.tipa_proof.num_rounds() != num_rounds
+        || proof.gipa_point_proof.num_rounds() != num_rounds
+    {
+        return Ok(false);
+    }
+
+    // Random linear combination of openings
+    let r = opening_challenge::<P, D>(kzg_vk, openings, &proof.com_proofs)?;
+    let r_vec = structured_scalar_power(openings.len(), &r);
+    let point_r_vec = point_scalars(openings, &r_vec);
+
+    // Check aggregates of the committed proofs
+    let tipa_proof_valid = MultiExpInnerProduct::<P, D>::verify_with_structured_scalar_message(
+        ip_verifier_srs,
+        &HomomorphicPlaceholderValue,
+        (&proof.com_proofs, &IdentityOutput(vec![proof.agg_proof.clone()])),
+        &r,
+        &proof.tipa_proof,
+    )?;
+    let gipa_point_proof_valid = PublicMultiExpInnerProduct::<P, D>::verify_with_context(
+        (ip_verifier_srs, &(), &()),
+        &HomomorphicPlaceholderValue,
+        (
+            &proof.com_proofs,
+            &IdentityOutput(point_r_vec),
+            &IdentityOutput(vec![proof.agg_point_proof.clone()]),
+        ),
+        &proof.gipa_point_proof,
+        &<P::Fr>::one(),
+        &gipa_context::<P, D>(ip_verifier_srs, &r)?,
+    )?;
+
+    // Check aggregate KZG equation
+    let agg_com = MultiexponentiationInnerProduct::<P::G1Projective>::inner_product(
+        &openings.iter().map(|o| o.com.clone()).collect::<Vec<_>>(),
+        &r_vec,
+    )?;
+    let agg_eval = ScalarInnerProduct::<P::Fr>::inner_product(
+        &openings.iter().map(|o| o.eval.clone()).collect::<Vec<_>>(),
+        &r_vec,
+    )?;
+    let kzg_valid = P::pairing(
+        agg_com - &kzg_vk.g.mul(&agg_eval) + &proof.agg_point_proof,
+        kzg_vk.h.clone(),
+    ) == P::pairing(proof.agg_proof.clone(), kzg_vk.h_tau.clone());
+
+    Ok(tipa_proof_valid && gipa_point_proof_valid && kzg_valid)
+}
+
+// Binds the KZG verifying key, the openings and the commitment to the opening proofs
+fn opening_challenge<P: PairingEngine, D: Digest>(
+    kzg_vk: &KZGVerifierKey<P>,
+    openings: &[KZGOpening<P>],
+    com_proofs: &ExtensionFieldElement<P>,
+) -> Result<P::Fr, Error> {
+    //TODO: Should use CanonicalSerialize instead of ToBytes
+    let mut bytes = to_bytes![
+        kzg_vk.g,
+        kzg_vk.h,
+        kzg_vk.h_tau,
+        openings.len() as u64,
+        com_proofs
+    ]?;
+    for opening in openings.iter() {
+        bytes.extend(to_bytes![opening.com, opening.point, opening.eval]?);
+    }
+    Ok(hash_to_challenge::<P::Fr, D>(&bytes))
+}
+
+// Binds the inner product SRS and the random linear combination to the transcript of the argument
+// for the opening points, whose scalars depend on r
+fn gipa_context<P: PairingEngine, D: Digest>(
+    ip_verifier_srs: &VerifierSRS<P>,
+    r: &P::Fr,
+) -> Result<Vec<u8>, Error> {
+    let srs_digest = ip_verifier_srs.digest::<D>()?;
+    //TODO: Should use CanonicalSerialize instead of ToBytes
+    Ok(to_bytes![srs_digest, r]?)
+}
+
+// Scalars r^i point_i of the opening proofs in the aggregate for the opening points
+fn point_scalars<P: PairingEngine>(openings: &[KZGOpening<P>], r_vec: &[P::Fr]) -> Vec<P::Fr> {
+    openings
+        .iter()
+        .zip(r_vec)
+        .map(|(opening, r)| opening.point * r)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::{curves::ProjectiveCurve, UniformRand};
+    use blake2::Blake2b;
+    use ff_fft::polynomial::DensePolynomial as UnivariatePolynomial;
+    use num_traits::identities::One;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{applications::poly_commit::KZG, tipa::structured_generators_scalar_power};
+
+    pairing_engine_tests! {
+        const NUM_OPENINGS_TO_AGGREGATE: usize = 8;
+        const DEGREE: usize = 15;
+
+        #[test]
+        fn aggregate_openings_test() {
+            type Fr = <E as PairingEngine>::Fr;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let g = <E as PairingEngine>::G1Projective::prime_subgroup_generator();
+            let h = <E as PairingEngine>::G2Projective::prime_subgroup_generator();
+            let tau = Fr::rand(&mut rng);
+            let powers = structured_generators_scalar_power(DEGREE + 1, &g, &tau);
+            let kzg_vk = KZGVerifierKey::<E> {
+                g: g.clone(),
+                h: h.clone(),
+                h_tau: <<E as PairingEngine>::G2Projective as Group>::mul(&h, &tau),
+            };
+            let srs =
+                setup_inner_product::<E, Blake2b, _>(&mut rng, NUM_OPENINGS_TO_AGGREGATE).unwrap();
+            let v_srs = srs.get_verifier_key();
+
+            // Open pairs of polynomials at a shared point
+            let mut openings = Vec::new();
+            let mut proofs = Vec::new();
+            let points = (0..NUM_OPENINGS_TO_AGGREGATE / 2)
+                .map(|_| Fr::rand(&mut rng))
+                .collect::<Vec<_>>();
+            for i in 0..NUM_OPENINGS_TO_AGGREGATE {
+                let point = points[i / 2].clone();
+                let polynomial = UnivariatePolynomial::rand(DEGREE, &mut rng);
+                openings.push(KZGOpening {
+                    com: KZG::<E>::commit(&powers, &polynomial).unwrap(),
+                    point: point.clone(),
+                    eval: polynomial.evaluate(point.clone()),
+                });
+                proofs.push(KZG::<E>::open(&powers, &polynomial, &point).unwrap());
+            }
+
+            let aggregate_proof =
+                aggregate_openings::<E, Blake2b>(&srs, &kzg_vk, &openings, &proofs).unwrap();
+            assert!(
+                verify_aggregate_openings(&v_srs, &kzg_vk, &openings, &aggregate_proof).unwrap()
+            );
+
+            // Wrong evaluation
+            let mut wrong_openings = openings.clone();
+            wrong_openings[1].eval += &Fr::one();
+            assert!(!verify_aggregate_openings(
+                &v_srs,
+                &kzg_vk,
+                &wrong_openings,
+                &aggregate_proof
+            )
+            .unwrap());
+
+            // Wrong opening proof
+            let mut wrong_proofs = proofs.clone();
+            wrong_proofs[2] = proofs[3].clone();
+            let wrong_aggregate_proof =
+                aggregate_openings::<E, Blake2b>(&srs, &kzg_vk, &openings, &wrong_proofs).unwrap();
+            assert!(!verify_aggregate_openings(
+                &v_srs,
+                &kzg_vk,
+                &openings,
+                &wrong_aggregate_proof
+            )
+            .unwrap());
+
+            // Proof for a different number of openings
+            assert!(!verify_aggregate_openings(
+                &v_srs,
+                &kzg_vk,
+                &openings[..NUM_OPENINGS_TO_AGGREGATE / 2],
+                &aggregate_proof
+            )
+            .unwrap());
+            assert!(!verify_aggregate_openings(&v_srs, &kzg_vk, &[], &aggregate_proof).unwrap());
+        }
+    }
+}
@@ -1 +1,3 @@
-pub mod groth16_aggregation;
\ No newline at end of file
+pub mod groth16_aggregation;
+pub mod kzg_aggregation;
+pub mod poly_commit;
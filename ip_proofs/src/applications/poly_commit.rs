@@ -0,0 +1,773 @@
+use algebra::{
+    batch_inversion,
+    bytes::ToBytes,
+    curves::{PairingEngine, ProjectiveCurve},
+    fields::Field,
+    groups::Group,
+    to_bytes, UniformRand,
+};
+use digest::Digest;
+use ff_fft::polynomial::DensePolynomial as UnivariatePolynomial;
+use num_traits::identities::{One, Zero};
+use rand::Rng;
+use std::marker::PhantomData;
+
+use dh_commitments::{
+    afgho16::AFGHOCommitmentG1,
+    identity::{HomomorphicPlaceholderValue, IdentityCommitment, IdentityOutput},
+    DoublyHomomorphicCommitment,
+};
+use inner_products::{ExtensionFieldElement, InnerProduct, MultiexponentiationInnerProduct};
+use crate::{
+    fiat_shamir::hash_to_challenge,
+    tipa::{
+        structured_generators_scalar_power,
+        structured_scalar_message::{structured_scalar_power, TIPAWithSSM, TIPAWithSSMProof},
+        SRSPowers, VerifierSRS, SRS,
+    },
+    mul_helper, Error, InnerProductArgumentError,
+};
+
+type PolynomialCommitmentTIPA<P, D> = TIPAWithSSM<
+    MultiexponentiationInnerProduct<<P as PairingEngine>::G1Projective>,
+    AFGHOCommitmentG1<P>,
+    IdentityCommitment<<P as PairingEngine>::G1Projective, <P as PairingEngine>::Fr>,
+    P,
+    D,
+>;
+
+type PolynomialCommitmentTIPAProof<P, D> = TIPAWithSSMProof<
+    MultiexponentiationInnerProduct<<P as PairingEngine>::G1Projective>,
+    AFGHOCommitmentG1<P>,
+    IdentityCommitment<<P as PairingEngine>::G1Projective, <P as PairingEngine>::Fr>,
+    P,
+    D,
+>;
+
+pub struct KZG<P: PairingEngine> {
+    _pair: PhantomData<P>,
+}
+
+// KZG commitment over powers g^{alpha^i}, verified against h and h^alpha from the TIPA verifier SRS
+impl<P: PairingEngine> KZG<P> {
+    pub fn commit(
+        powers: &[P::G1Projective],
+        polynomial: &UnivariatePolynomial<P::Fr>,
+    ) -> Result<P::G1Projective, Error> {
+        if polynomial.coeffs.len() > powers.len() {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                polynomial.coeffs.len(),
+                powers.len(),
+            )));
+        }
+        let mut coeffs = polynomial.coeffs.clone();
+        coeffs.resize(powers.len(), <P::Fr>::zero());
+        Ok(MultiexponentiationInnerProduct::<P::G1Projective>::inner_product(powers, &coeffs)?)
+    }
+
+    pub fn open(
+        powers: &[P::G1Projective],
+        polynomial: &UnivariatePolynomial<P::Fr>,
+        point: &P::Fr,
+    ) -> Result<P::G1Projective, Error> {
+        if polynomial.coeffs.len() > powers.len() {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                polynomial.coeffs.len(),
+                powers.len(),
+            )));
+        }
+        let eval = polynomial.evaluate(point.clone());
+        let quotient_polynomial = &(polynomial
+            - &UnivariatePolynomial::from_coefficients_vec(vec![eval]))
+            / &(UnivariatePolynomial::from_coefficients_vec(vec![
+                -point.clone(),
+                <P::Fr>::one(),
+            ]));
+        Self::commit(powers, &quotient_polynomial)
+    }
+
+    pub fn verify(
+        v_srs: &VerifierSRS<P>,
+        com: &P::G1Projective,
+        point: &P::Fr,
+        eval: &P::Fr,
+        proof: &P::G1Projective,
+    ) -> Result<bool, Error> {
+        Ok(
+            P::pairing(com.clone() - &v_srs.g.mul(eval.clone()), v_srs.h.clone())
+                == P::pairing(
+                    proof.clone(),
+                    v_srs.h_alpha.clone() - &v_srs.h.mul(point.clone()),
+                ),
+        )
+    }
+}
+
+pub struct BivariatePolynomial<F: Field> {
+    y_polynomials: Vec<UnivariatePolynomial<F>>,
+}
+
+impl<F: Field> BivariatePolynomial<F> {
+    pub fn new(y_polynomials: Vec<UnivariatePolynomial<F>>) -> Self {
+        Self { y_polynomials }
+    }
+
+    pub fn evaluate(&self, point: &(F, F)) -> F {
+        let (x, y) = point;
+        let mut x_power = F::one();
+        let mut eval = F::zero();
+        for y_polynomial in self.y_polynomials.iter() {
+            eval += &(x_power * &y_polynomial.evaluate(y.clone()));
+            x_power *= x;
+        }
+        eval
+    }
+}
+
+pub struct OpeningProof<P: PairingEngine, D: Digest> {
+    ip_proof: PolynomialCommitmentTIPAProof<P, D>,
+    y_eval_comm: P::G1Projective,
+    kzg_proof: P::G1Projective,
+}
+
+pub struct BatchOpeningProof<P: PairingEngine, D: Digest> {
+    h_comm: ExtensionFieldElement<P>,
+    opening_proof: OpeningProof<P, D>,
+}
+
+// Commitment to a bivariate polynomial: KZG commitments to each polynomial in Y (one per power of X),
+// combined by an AFGHO commitment. Opening at (x, y) proves the KZG commitment to f(x, Y) with
+// TIPA for the structured scalar message (1, x, x^2, ...) and then opens it at y with KZG.
+pub struct BivariatePolynomialCommitment<P: PairingEngine, D: Digest> {
+    _pair: PhantomData<P>,
+    _digest: PhantomData<D>,
+}
+
+impl<P: PairingEngine, D: Digest> BivariatePolynomialCommitment<P, D> {
+    pub fn setup<R: Rng>(
+        rng: &mut R,
+        x_degree: usize,
+        y_degree: usize,
+    ) -> Result<(SRS<P>, Vec<P::G1Projective>), Error> {
+        if !(x_degree + 1).is_power_of_two() {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                x_degree + 1,
+                (x_degree + 1).next_power_of_two(),
+            )));
+        }
+        let alpha = <P::Fr>::rand(rng);
+        let beta = <P::Fr>::rand(rng);
+        let g = <P::G1Projective>::prime_subgroup_generator();
+        let h = <P::G2Projective>::prime_subgroup_generator();
+        // Only the h^{beta^i} powers are needed by TIPA with structured scalar message; the powers of
+        // alpha in G1 are used for the KZG commitments to the polynomials in Y
+        let srs = SRS {
+            g_alpha_powers: SRSPowers::default(),
+            h_beta_powers: SRSPowers::new(structured_generators_scalar_power(
+                2 * x_degree + 1,
+                &h,
+                &beta,
+            )),
+            g_beta_powers: SRSPowers::default(),
+            h_alpha_powers: SRSPowers::default(),
+            g_alpha: <P::G1Projective as Group>::mul(&g, &alpha),
+            g_beta: <P::G1Projective as Group>::mul(&g, &beta),
+            h_alpha: <P::G2Projective as Group>::mul(&h, &alpha),
+            h_beta: <P::G2Projective as Group>::mul(&h, &beta),
+        };
+        let kzg_srs = structured_generators_scalar_power(y_degree + 1, &g, &alpha);
+        Ok((srs, kzg_srs))
+    }
+
+    pub fn commit(
+        srs: &(SRS<P>, Vec<P::G1Projective>),
+        bivariate_polynomial: &BivariatePolynomial<P::Fr>,
+    ) -> Result<(ExtensionFieldElement<P>, Vec<P::G1Projective>), Error> {
+        let (ip_srs, kzg_srs) = srs;
+        let (ck, _) = ip_srs.get_commitment_keys();
+        if bivariate_polynomial.y_polynomials.len() > ck.len() {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                bivariate_polynomial.y_polynomials.len(),
+                ck.len(),
+            )));
+        }
+
+        // Create KZG commitments to Y polynomials
+        let y_polynomial_comms = bivariate_polynomial
+            .y_polynomials
+            .iter()
+            .chain([UnivariatePolynomial::zero()].iter().cycle())
+            .take(ck.len())
+            .map(|y_polynomial| KZG::<P>::commit(kzg_srs, y_polynomial))
+            .collect::<Result<Vec<P::G1Projective>, Error>>()?;
+
+        // Create AFGHO commitment to Y polynomial commitments
+        Ok((
+            AFGHOCommitmentG1::<P>::commit(&ck, &y_polynomial_comms)?,
+            y_polynomial_comms,
+        ))
+    }
+
+    pub fn open(
+        srs: &(SRS<P>, Vec<P::G1Projective>),
+        bivariate_polynomial: &BivariatePolynomial<P::Fr>,
+        y_polynomial_comms: &[P::G1Projective],
+        point: &(P::Fr, P::Fr),
+    ) -> Result<OpeningProof<P, D>, Error> {
+        let (x, y) = point;
+        let (ip_srs, kzg_srs) = srs;
+        let (ck, _) = ip_srs.get_commitment_keys();
+        if bivariate_polynomial.y_polynomials.len() > ck.len() {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                bivariate_polynomial.y_polynomials.len(),
+                ck.len(),
+            )));
+        }
+
+        // Prove KZG commitment to f(x, Y) from Y polynomial commitments
+        let powers_of_x = structured_scalar_power(ck.len(), x);
+        let y_eval_comm = MultiexponentiationInnerProduct::<P::G1Projective>::inner_product(
+            y_polynomial_comms,
+            &powers_of_x,
+        )?;
+        let ip_proof = PolynomialCommitmentTIPA::<P, D>::prove_with_structured_scalar_message(
+            ip_srs,
+            (y_polynomial_comms, &powers_of_x),
+            (&ck, &HomomorphicPlaceholderValue),
+            x,
+        )?;
+
+        // KZG opening of f(x, Y) at y
+        let mut y_eval_coeffs = vec![<P::Fr>::zero(); kzg_srs.len()];
+        for (y_polynomial, x_power) in bivariate_polynomial.y_polynomials.iter().zip(&powers_of_x) {
+            for (coeff, y_coeff) in y_eval_coeffs.iter_mut().zip(&y_polynomial.coeffs) {
+                *coeff += &(x_power.clone() * y_coeff);
+            }
+        }
+        let kzg_proof = KZG::<P>::open(
+            kzg_srs,
+            &UnivariatePolynomial::from_coefficients_vec(y_eval_coeffs),
+            y,
+        )?;
+
+        Ok(OpeningProof {
+            ip_proof,
+            y_eval_comm,
+            kzg_proof,
+        })
+    }
+
+    pub fn verify(
+        v_srs: &VerifierSRS<P>,
+        com: &ExtensionFieldElement<P>,
+        point: &(P::Fr, P::Fr),
+        eval: &P::Fr,
+        proof: &OpeningProof<P, D>,
+    ) -> Result<bool, Error> {
+        let (x, y) = point;
+        let ip_proof_valid =
+            PolynomialCommitmentTIPA::<P, D>::verify_with_structured_scalar_message(
+                v_srs,
+                &HomomorphicPlaceholderValue,
+                (com, &IdentityOutput(vec![proof.y_eval_comm.clone()])),
+                x,
+                &proof.ip_proof,
+            )?;
+        let kzg_proof_valid =
+            KZG::<P>::verify(v_srs, &proof.y_eval_comm, y, eval, &proof.kzg_proof)?;
+        Ok(ip_proof_valid && kzg_proof_valid)
+    }
+}
+
+// Univariate polynomial of degree d viewed as a bivariate polynomial f(X, Y) with X = Y^m and
+// m ~ sqrt(d), so that both the TIPA SRS and the KZG SRS are of size O(sqrt(d))
+pub struct UnivariatePolynomialCommitment<P: PairingEngine, D: Digest> {
+    _pair: PhantomData<P>,
+    _digest: PhantomData<D>,
+}
+
+impl<P: PairingEngine, D: Digest> UnivariatePolynomialCommitment<P, D> {
+    fn bivariate_degrees(univariate_degree: usize) -> (usize, usize) {
+        let x_len = (((univariate_degree + 1) as f64).sqrt().ceil() as usize).next_power_of_two();
+        let y_len = (univariate_degree + x_len) / x_len;
+        (x_len - 1, y_len - 1)
+    }
+
+    fn parse_bivariate_degrees_from_srs(srs: &(SRS<P>, Vec<P::G1Projective>)) -> (usize, usize) {
+        let x_degree = (srs.0.h_beta_powers.len() - 1) / 2;
+        let y_degree = srs.1.len() - 1;
+        (x_degree, y_degree)
+    }
+
+    fn bivariate_form(
+        bivariate_degrees: (usize, usize),
+        polynomial: &UnivariatePolynomial<P::Fr>,
+    ) -> Result<BivariatePolynomial<P::Fr>, Error> {
+        let (x_degree, y_degree) = bivariate_degrees;
+        if polynomial.coeffs.len() > (x_degree + 1) * (y_degree + 1) {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                polynomial.coeffs.len(),
+                (x_degree + 1) * (y_degree + 1),
+            )));
+        }
+        Ok(BivariatePolynomial::new(
+            polynomial
+                .coeffs
+                .chunks(y_degree + 1)
+                .map(UnivariatePolynomial::from_coefficients_slice)
+                .collect(),
+        ))
+    }
+
+    pub fn setup<R: Rng>(
+        rng: &mut R,
+        degree: usize,
+    ) -> Result<(SRS<P>, Vec<P::G1Projective>), Error> {
+        let (x_degree, y_degree) = Self::bivariate_degrees(degree);
+        BivariatePolynomialCommitment::<P, D>::setup(rng, x_degree, y_degree)
+    }
+
+    pub fn commit(
+        srs: &(SRS<P>, Vec<P::G1Projective>),
+        polynomial: &UnivariatePolynomial<P::Fr>,
+    ) -> Result<(ExtensionFieldElement<P>, Vec<P::G1Projective>), Error> {
+        let bivariate_degrees = Self::parse_bivariate_degrees_from_srs(srs);
+        BivariatePolynomialCommitment::<P, D>::commit(
+            srs,
+            &Self::bivariate_form(bivariate_degrees, polynomial)?,
+        )
+    }
+
+    pub fn open(
+        srs: &(SRS<P>, Vec<P::G1Projective>),
+        polynomial: &UnivariatePolynomial<P::Fr>,
+        y_polynomial_comms: &[P::G1Projective],
+        point: &P::Fr,
+    ) -> Result<OpeningProof<P, D>, Error> {
+        let (x_degree, y_degree) = Self::parse_bivariate_degrees_from_srs(srs);
+        let y = point.clone();
+        let x = point.pow(&[(y_degree + 1) as u64]);
+        BivariatePolynomialCommitment::<P, D>::open(
+            srs,
+            &Self::bivariate_form((x_degree, y_degree), polynomial)?,
+            y_polynomial_comms,
+            &(x, y),
+        )
+    }
+
+    // The maximum degree must be the one the SRS was set up for
+    pub fn verify(
+        v_srs: &VerifierSRS<P>,
+        max_degree: usize,
+        com: &ExtensionFieldElement<P>,
+        point: &P::Fr,
+        eval: &P::Fr,
+        proof: &OpeningProof<P, D>,
+    ) -> Result<bool, Error> {
+        let (x_degree, y_degree) = Self::bivariate_degrees(max_degree);
+        if x_degree + 1 != v_srs.max_size() {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                x_degree + 1,
+                v_srs.max_size(),
+            )));
+        }
+        let y = point.clone();
+        let x = point.pow(&[(y_degree + 1) as u64]);
+        BivariatePolynomialCommitment::<P, D>::verify(v_srs, com, &(x, y), eval, proof)
+    }
+}
+
+impl<P: PairingEngine, D: Digest> UnivariatePolynomialCommitment<P, D> {
+    // Opens each query (i, z) of polynomial i at point z with a single proof. Queries are folded with
+    // powers of a challenge rho into h(X) = sum_q rho^q (f_q(X) - f_q(z_q)) / (X - z_q); after
+    // committing to h, the verifier derives a commitment to
+    //   L(X) = sum_q rho^q / (zeta - z_q) * (f_q(X) - f_q(z_q)) - h(X)
+    // from the input commitments, which is opened to 0 at a random point zeta with a single opening.
+    pub fn batch_open(
+        srs: &(SRS<P>, Vec<P::G1Projective>),
+        polynomials: &[UnivariatePolynomial<P::Fr>],
+        coms: &[ExtensionFieldElement<P>],
+        queries: &[(usize, P::Fr)],
+    ) -> Result<(Vec<P::Fr>, BatchOpeningProof<P, D>), Error> {
+        if polynomials.len() != coms.len() {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                polynomials.len(),
+                coms.len(),
+            )));
+        }
+        Self::check_queries(polynomials.len(), queries, queries.len())?;
+        let evals = queries
+            .iter()
+            .map(|(i, z)| polynomials[*i].evaluate(z.clone()))
+            .collect::<Vec<P::Fr>>();
+        let max_len = polynomials
+            .iter()
+            .map(|polynomial| polynomial.coeffs.len())
+            .max()
+            .unwrap()
+            .max(1);
+
+        // Random linear combination of quotient polynomials
+        let rho = Self::batch_query_challenge(coms, queries, &evals)?;
+        let rho_powers = structured_scalar_power(queries.len(), &rho);
+        let mut h_coeffs = vec![<P::Fr>::zero(); max_len];
+        for (((i, z), eval), rho_power) in queries.iter().zip(&evals).zip(&rho_powers) {
+            let quotient_polynomial = &(&polynomials[*i]
+                - &UnivariatePolynomial::from_coefficients_vec(vec![eval.clone()]))
+                / &(UnivariatePolynomial::from_coefficients_vec(vec![
+                    -z.clone(),
+                    <P::Fr>::one(),
+                ]));
+            for (h_coeff, coeff) in h_coeffs.iter_mut().zip(&quotient_polynomial.coeffs) {
+                *h_coeff += &(coeff.clone() * rho_power);
+            }
+        }
+        let h_polynomial = UnivariatePolynomial::from_coefficients_vec(h_coeffs);
+        let (h_comm, _) = Self::commit(srs, &h_polynomial)?;
+
+        // Fold queries into a single polynomial vanishing at zeta
+        let zeta = Self::batch_point_challenge(&rho, &h_comm)?;
+        let query_coeffs = Self::batch_query_coefficients(queries, &rho_powers, &zeta);
+        let mut l_coeffs = h_polynomial
+            .coeffs
+            .iter()
+            .map(|coeff| -coeff.clone())
+            .collect::<Vec<P::Fr>>();
+        l_coeffs.resize(max_len, <P::Fr>::zero());
+        for (((i, _), eval), query_coeff) in queries.iter().zip(&evals).zip(&query_coeffs) {
+            for (l_coeff, coeff) in l_coeffs.iter_mut().zip(&polynomials[*i].coeffs) {
+                *l_coeff += &(coeff.clone() * query_coeff);
+            }
+            l_coeffs[0] -= &(eval.clone() * query_coeff);
+        }
+        let l_polynomial = UnivariatePolynomial::from_coefficients_vec(l_coeffs);
+        let (_, l_y_polynomial_comms) = Self::commit(srs, &l_polynomial)?;
+        let opening_proof = Self::open(srs, &l_polynomial, &l_y_polynomial_comms, &zeta)?;
+
+        Ok((
+            evals,
+            BatchOpeningProof {
+                h_comm,
+                opening_proof,
+            },
+        ))
+    }
+
+    pub fn batch_verify(
+        v_srs: &VerifierSRS<P>,
+        max_degree: usize,
+        coms: &[ExtensionFieldElement<P>],
+        queries: &[(usize, P::Fr)],
+        evals: &[P::Fr],
+        proof: &BatchOpeningProof<P, D>,
+    ) -> Result<bool, Error> {
+        Self::check_queries(coms.len(), queries, evals.len())?;
+        let rho = Self::batch_query_challenge(coms, queries, evals)?;
+        let rho_powers = structured_scalar_power(queries.len(), &rho);
+        let zeta = Self::batch_point_challenge(&rho, &proof.h_comm)?;
+        let query_coeffs = Self::batch_query_coefficients(queries, &rho_powers, &zeta);
+
+        // Commitment to L(X); evaluations are subtracted from the constant term, which is
+        // committed to by e(g, h) in the first row
+        let mut l_comm = mul_helper(&proof.h_comm, &-<P::Fr>::one());
+        let mut eval_combination = <P::Fr>::zero();
+        for (((i, _), eval), query_coeff) in queries.iter().zip(evals).zip(&query_coeffs) {
+            l_comm = l_comm + mul_helper(&coms[*i], query_coeff);
+            eval_combination += &(eval.clone() * query_coeff);
+        }
+        l_comm = l_comm
+            + ExtensionFieldElement(P::pairing(
+                v_srs.g.mul(-eval_combination),
+                v_srs.h.clone(),
+            ));
+
+        Self::verify(
+            v_srs,
+            max_degree,
+            &l_comm,
+            &zeta,
+            &<P::Fr>::zero(),
+            &proof.opening_proof,
+        )
+    }
+
+    // Queries must be non-empty, have one evaluation each and refer to one of the polynomials
+    fn check_queries(
+        num_polynomials: usize,
+        queries: &[(usize, P::Fr)],
+        num_evals: usize,
+    ) -> Result<(), Error> {
+        if queries.is_empty() || queries.len() != num_evals {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                queries.len(),
+                num_evals,
+            )));
+        }
+        if let Some((i, _)) = queries.iter().find(|(i, _)| *i >= num_polynomials) {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                *i,
+                num_polynomials,
+            )));
+        }
+        Ok(())
+    }
+
+    fn batch_query_coefficients(
+        queries: &[(usize, P::Fr)],
+        rho_powers: &[P::Fr],
+        zeta: &P::Fr,
+    ) -> Vec<P::Fr> {
+        let mut query_coeffs = queries
+            .iter()
+            .map(|(_, z)| zeta.clone() - z)
+            .collect::<Vec<P::Fr>>();
+        batch_inversion(&mut query_coeffs);
+        query_coeffs
+            .iter()
+            .zip(rho_powers)
+            .map(|(coeff, rho_power)| coeff.clone() * rho_power)
+            .collect()
+    }
+
+    fn batch_query_challenge(
+        coms: &[ExtensionFieldElement<P>],
+        queries: &[(usize, P::Fr)],
+        evals: &[P::Fr],
+    ) -> Result<P::Fr, Error> {
+        let mut hash_input = Vec::new();
+        //TODO: Should use CanonicalSerialize instead of ToBytes
+        for com in coms.iter() {
+            hash_input.extend_from_slice(&to_bytes![com]?);
+        }
+        for ((i, z), eval) in queries.iter().zip(evals) {
+            hash_input.extend_from_slice(&to_bytes![*i as u64, z, eval]?);
+        }
+        Ok(hash_to_challenge::<P::Fr, D>(&hash_input))
+    }
+
+    fn batch_point_challenge(
+        rho: &P::Fr,
+        h_comm: &ExtensionFieldElement<P>,
+    ) -> Result<P::Fr, Error> {
+        Ok(hash_to_challenge::<P::Fr, D>(&to_bytes![rho, h_comm]?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blake2::Blake2b;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    pairing_engine_tests! {
+        type TestBivariatePolyCommitment = BivariatePolynomialCommitment<E, Blake2b>;
+        type TestUnivariatePolyCommitment = UnivariatePolynomialCommitment<E, Blake2b>;
+
+        const BIVARIATE_X_DEGREE: usize = 7;
+        const BIVARIATE_Y_DEGREE: usize = 7;
+        const UNIVARIATE_DEGREE: usize = 100;
+
+        #[test]
+        fn bivariate_poly_commit_test() {
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let srs =
+                TestBivariatePolyCommitment::setup(&mut rng, BIVARIATE_X_DEGREE, BIVARIATE_Y_DEGREE)
+                    .unwrap();
+            let v_srs = srs.0.get_verifier_key();
+
+            let mut y_polynomials = Vec::new();
+            for _ in 0..BIVARIATE_X_DEGREE + 1 {
+                y_polynomials.push(UnivariatePolynomial::rand(BIVARIATE_Y_DEGREE, &mut rng));
+            }
+            let bivariate_polynomial = BivariatePolynomial::new(y_polynomials);
+
+            // Commit to polynomial
+            let (com, y_polynomial_comms) =
+                TestBivariatePolyCommitment::commit(&srs, &bivariate_polynomial).unwrap();
+
+            // Evaluate at challenge point
+            let point = (
+                <E as PairingEngine>::Fr::rand(&mut rng),
+                <E as PairingEngine>::Fr::rand(&mut rng),
+            );
+            let eval_proof = TestBivariatePolyCommitment::open(
+                &srs,
+                &bivariate_polynomial,
+                &y_polynomial_comms,
+                &point,
+            )
+            .unwrap();
+            let eval = bivariate_polynomial.evaluate(&point);
+
+            // Verify proof
+            assert!(
+                TestBivariatePolyCommitment::verify(&v_srs, &com, &point, &eval, &eval_proof).unwrap()
+            );
+
+            // Sizes not supported by the SRS are rejected with an error
+            assert!(TestBivariatePolyCommitment::setup(
+                &mut rng,
+                BIVARIATE_X_DEGREE - 1,
+                BIVARIATE_Y_DEGREE
+            )
+            .is_err());
+            let mut large_y_polynomials = Vec::new();
+            for _ in 0..BIVARIATE_X_DEGREE + 1 {
+                large_y_polynomials
+                    .push(UnivariatePolynomial::rand(BIVARIATE_Y_DEGREE + 1, &mut rng));
+            }
+            assert!(TestBivariatePolyCommitment::commit(
+                &srs,
+                &BivariatePolynomial::new(large_y_polynomials)
+            )
+            .is_err());
+        }
+
+        #[test]
+        fn univariate_poly_commit_test() {
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let srs = TestUnivariatePolyCommitment::setup(&mut rng, UNIVARIATE_DEGREE).unwrap();
+            let v_srs = srs.0.get_verifier_key();
+
+            let polynomial = UnivariatePolynomial::rand(UNIVARIATE_DEGREE, &mut rng);
+
+            // Commit to polynomial
+            let (com, y_polynomial_comms) =
+                TestUnivariatePolyCommitment::commit(&srs, &polynomial).unwrap();
+
+            // Evaluate at challenge point
+            let point = <E as PairingEngine>::Fr::rand(&mut rng);
+            let eval_proof =
+                TestUnivariatePolyCommitment::open(&srs, &polynomial, &y_polynomial_comms, &point)
+                    .unwrap();
+            let eval = polynomial.evaluate(point.clone());
+
+            // Verify proof
+            assert!(TestUnivariatePolyCommitment::verify(
+                &v_srs,
+                UNIVARIATE_DEGREE,
+                &com,
+                &point,
+                &eval,
+                &eval_proof
+            )
+            .unwrap());
+
+            // Wrong evaluation
+            let wrong_eval = eval + &<E as PairingEngine>::Fr::one();
+            assert!(!TestUnivariatePolyCommitment::verify(
+                &v_srs,
+                UNIVARIATE_DEGREE,
+                &com,
+                &point,
+                &wrong_eval,
+                &eval_proof
+            )
+            .unwrap());
+
+            // Degrees not supported by the SRS are rejected with an error
+            let large_polynomial = UnivariatePolynomial::rand(4 * UNIVARIATE_DEGREE, &mut rng);
+            assert!(TestUnivariatePolyCommitment::commit(&srs, &large_polynomial).is_err());
+            assert!(TestUnivariatePolyCommitment::verify(
+                &v_srs,
+                4 * UNIVARIATE_DEGREE,
+                &com,
+                &point,
+                &eval,
+                &eval_proof
+            )
+            .is_err());
+        }
+
+        #[test]
+        fn univariate_poly_commit_batch_open_test() {
+            const NUM_POLYNOMIALS: usize = 3;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let srs = TestUnivariatePolyCommitment::setup(&mut rng, UNIVARIATE_DEGREE).unwrap();
+            let v_srs = srs.0.get_verifier_key();
+
+            let mut polynomials = Vec::new();
+            let mut coms = Vec::new();
+            for _ in 0..NUM_POLYNOMIALS {
+                let polynomial = UnivariatePolynomial::rand(UNIVARIATE_DEGREE, &mut rng);
+                coms.push(TestUnivariatePolyCommitment::commit(&srs, &polynomial).unwrap().0);
+                polynomials.push(polynomial);
+            }
+
+            // Open every polynomial at a shared point and at a point of its own
+            let shared_point = <E as PairingEngine>::Fr::rand(&mut rng);
+            let mut queries = Vec::new();
+            for i in 0..NUM_POLYNOMIALS {
+                queries.push((i, shared_point.clone()));
+                queries.push((i, <E as PairingEngine>::Fr::rand(&mut rng)));
+            }
+            let (evals, proof) =
+                TestUnivariatePolyCommitment::batch_open(&srs, &polynomials, &coms, &queries).unwrap();
+            for ((i, z), eval) in queries.iter().zip(&evals) {
+                assert_eq!(polynomials[*i].evaluate(z.clone()), *eval);
+            }
+
+            assert!(TestUnivariatePolyCommitment::batch_verify(
+                &v_srs,
+                UNIVARIATE_DEGREE,
+                &coms,
+                &queries,
+                &evals,
+                &proof
+            )
+            .unwrap());
+
+            // Wrong evaluation
+            let mut wrong_evals = evals.clone();
+            wrong_evals[1] += &<E as PairingEngine>::Fr::one();
+            assert!(!TestUnivariatePolyCommitment::batch_verify(
+                &v_srs,
+                UNIVARIATE_DEGREE,
+                &coms,
+                &queries,
+                &wrong_evals,
+                &proof
+            )
+            .unwrap());
+
+            // Malformed queries are rejected with an error
+            let mut wrong_queries = queries.clone();
+            wrong_queries[0].0 = NUM_POLYNOMIALS;
+            assert!(TestUnivariatePolyCommitment::batch_verify(
+                &v_srs,
+                UNIVARIATE_DEGREE,
+                &coms,
+                &wrong_queries,
+                &evals,
+                &proof
+            )
+            .is_err());
+            assert!(TestUnivariatePolyCommitment::batch_verify(
+                &v_srs,
+                UNIVARIATE_DEGREE,
+                &coms,
+                &queries,
+                &evals[1..],
+                &proof
+            )
+            .is_err());
+            assert!(TestUnivariatePolyCommitment::batch_verify(
+                &v_srs,
+                UNIVARIATE_DEGREE,
+                &coms,
+                &[],
+                &[],
+                &proof
+            )
+            .is_err());
+            assert!(TestUnivariatePolyCommitment::batch_open(
+                &srs,
+                &polynomials,
+                &coms,
+                &wrong_queries
+            )
+            .is_err());
+        }
+    }
+}
@@ -0,0 +1,877 @@
+use algebra::{
+    bytes::ToBytes,
+    curves::ProjectiveCurve,
+    fields::{Field, PrimeField},
+    to_bytes,
+};
+use digest::Digest;
+use num_traits::identities::One;
+use std::{
+    marker::PhantomData,
+    ops::{Add, MulAssign},
+};
+
+use crate::{
+    fiat_shamir::hash_to_challenge,
+    gipa::{linear_combination, GIPAProof, GIPA},
+    Error, InnerProductArgumentError,
+};
+use dh_commitments::{
+    identity::{HomomorphicPlaceholderValue, IdentityCommitment, IdentityOutput},
+    DoublyHomomorphicCommitment,
+};
+use inner_products::{InnerProduct, MultiexponentiationInnerProduct};
+
+// Proof that the final commitment key of GIPA was folded correctly from the initial commitment key.
+// The final key is ck_final = sum_i ck[i] * s[i], where s is the vector of folding coefficients of
+// the transcript (see key_folding_coefficients). The transcript holds the challenges in reverse order
+// as returned by GIPA, inverted by the caller for keys that are folded by inverse challenges (the
+// left commitment key). The challenge is derived by the caller from the GIPA transcript and final
+// keys and may be used by the proof, e.g. as the KZG opening point.
+pub trait CommitmentKeyProof {
+    type Scalar: PrimeField;
+    type Key: ToBytes
+        + Clone
+        + Default
+        + Eq
+        + Add<Self::Key, Output = Self::Key>
+        + MulAssign<Self::Scalar>;
+    type ProverKey;
+    type VerifierKey;
+    type Proof: Clone;
+
+    fn prove(
+        pk: &Self::ProverKey,
+        transcript: &[Self::Scalar],
+        r_shift: &Self::Scalar,
+        ck_final: &Self::Key,
+        challenge: &Self::Scalar,
+    ) -> Result<Self::Proof, Error>;
+
+    fn verify(
+        vk: &Self::VerifierKey,
+        transcript: &[Self::Scalar],
+        r_shift: &Self::Scalar,
+        ck_final: &Self::Key,
+        proof: &Self::Proof,
+        challenge: &Self::Scalar,
+    ) -> Result<bool, Error>;
+}
+
+// Coefficients s[i] = r^i * prod_j x_j^{i_j} of the initial commitment key in the final commitment key,
+// where i_j is the j-th bit of i and x_j = transcript[j]
+pub fn key_folding_coefficients<F: Field>(transcript: &[F], r_shift: &F) -> Vec<F> {
+    let mut coefficients = vec![F::one()];
+    let mut power_2_r = r_shift.clone();
+    for (i, x) in transcript.iter().enumerate() {
+        for j in 0..(2_usize).pow(i as u32) {
+            coefficients.push(coefficients[j] * &(x.clone() * &power_2_r));
+        }
+        power_2_r *= power_2_r;
+    }
+    coefficients
+}
+
+fn fold_public_message<M, F>(m: &[M], transcript: &[F]) -> Result<M, Error>
+where
+    M: MulAssign<F> + Clone + Add<M, Output = M>,
+    F: Field,
+{
+    let coefficients = key_folding_coefficients(transcript, &F::one());
+    if m.len() != coefficients.len() {
+        return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+            m.len(),
+            coefficients.len(),
+        )));
+    }
+    Ok(linear_combination(m, &coefficients))
+}
+
+// Transparent setup: the verifier holds the full commitment key and folds it itself in O(n)
+pub struct TransparentKeyProof<K, F> {
+    _key: PhantomData<K>,
+    _scalar: PhantomData<F>,
+}
+
+impl<K, F> CommitmentKeyProof for TransparentKeyProof<K, F>
+where
+    K: ToBytes + Clone + Default + Eq + Add<K, Output = K> + MulAssign<F>,
+    F: PrimeField,
+{
+    type Scalar = F;
+    type Key = K;
+    type ProverKey = ();
+    type VerifierKey = Vec<K>;
+    type Proof = ();
+
+    fn prove(
+        _pk: &Self::ProverKey,
+        _transcript: &[F],
+        _r_shift: &F,
+        _ck_final: &K,
+        _challenge: &F,
+    ) -> Result<Self::Proof, Error> {
+        Ok(())
+    }
+
+    fn verify(
+        vk: &Self::VerifierKey,
+        transcript: &[F],
+        r_shift: &F,
+        ck_final: &K,
+        _proof: &Self::Proof,
+        _challenge: &F,
+    ) -> Result<bool, Error> {
+        let coefficients = key_folding_coefficients(transcript, r_shift);
+        if vk.len() != coefficients.len() {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                vk.len(),
+                coefficients.len(),
+            )));
+        }
+        Ok(linear_combination(vk, &coefficients) == *ck_final)
+    }
+}
+
+// Keys of identity commitments are placeholders, so there is nothing to prove about the final key
+pub struct IdentityKeyProof<F> {
+    _scalar: PhantomData<F>,
+}
+
+impl<F: PrimeField> CommitmentKeyProof for IdentityKeyProof<F> {
+    type Scalar = F;
+    type Key = HomomorphicPlaceholderValue;
+    type ProverKey = ();
+    type VerifierKey = ();
+    type Proof = ();
+
+    fn prove(
+        _pk: &Self::ProverKey,
+        _transcript: &[F],
+        _r_shift: &F,
+        _ck_final: &Self::Key,
+        _challenge: &F,
+    ) -> Result<Self::Proof, Error> {
+        Ok(())
+    }
+
+    fn verify(
+        _vk: &Self::VerifierKey,
+        _transcript: &[F],
+        _r_shift: &F,
+        _ck_final: &Self::Key,
+        _proof: &Self::Proof,
+        _challenge: &F,
+    ) -> Result<bool, Error> {
+        Ok(true)
+    }
+}
+
+// Final right messages that are committed to (or public) are checked against their commitment, so
+// there is nothing to prove about them
+pub struct CommittedMessageProof<C> {
+    _commitment: PhantomData<C>,
+}
+
+impl<C: DoublyHomomorphicCommitment> CommitmentKeyProof for CommittedMessageProof<C> {
+    type Scalar = C::Scalar;
+    type Key = C::Message;
+    type ProverKey = ();
+    type VerifierKey = ();
+    type Proof = ();
+
+    fn prove(
+        _pk: &Self::ProverKey,
+        _transcript: &[C::Scalar],
+        _r_shift: &C::Scalar,
+        _ck_final: &Self::Key,
+        _challenge: &C::Scalar,
+    ) -> Result<Self::Proof, Error> {
+        Ok(())
+    }
+
+    fn verify(
+        _vk: &Self::VerifierKey,
+        _transcript: &[C::Scalar],
+        _r_shift: &C::Scalar,
+        _ck_final: &Self::Key,
+        _proof: &Self::Proof,
+        _challenge: &C::Scalar,
+    ) -> Result<bool, Error> {
+        Ok(true)
+    }
+}
+
+// Delegated key folding: the prover shows that the final commitment key is the multiexponentiation of
+// the commitment key with the folding coefficients, using GIPA with the commitment key as left
+// message committed under LMC. The verifier only holds the commitment to the commitment key and
+// computes the final folding coefficient in product form. The final key of this argument is in turn
+// proven by CKP, allowing recursion down to a key proof with the desired verifier cost.
+pub struct DelegatedKeyProof<LMC, CKP, D> {
+    _left_commitment: PhantomData<LMC>,
+    _key_proof: PhantomData<CKP>,
+    _digest: PhantomData<D>,
+}
+
+pub struct DelegatedKeyFoldingProof<LMC, CKP, D>
+where
+    D: Digest,
+    LMC: DoublyHomomorphicCommitment,
+    LMC::Message: ProjectiveCurve<ScalarField = LMC::Scalar>,
+    CKP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = LMC::Key>,
+{
+    gipa_proof: GIPAProof<
+        MultiexponentiationInnerProduct<LMC::Message>,
+        LMC,
+        IdentityCommitment<LMC::Scalar, LMC::Scalar>,
+        IdentityCommitment<LMC::Message, LMC::Scalar>,
+        D,
+    >,
+    final_ck: LMC::Key,
+    final_ck_proof: CKP::Proof,
+}
+
+impl<LMC, CKP, D> Clone for DelegatedKeyFoldingProof<LMC, CKP, D>
+    where
+        D: Digest,
+        LMC: DoublyHomomorphicCommitment,
+        LMC::Message: ProjectiveCurve<ScalarField = LMC::Scalar>,
+        CKP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = LMC::Key>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            gipa_proof: self.gipa_proof.clone(),
+            final_ck: self.final_ck.clone(),
+            final_ck_proof: self.final_ck_proof.clone(),
+        }
+    }
+}
+
+impl<LMC, CKP, D> DelegatedKeyProof<LMC, CKP, D>
+where
+    D: Digest,
+    LMC: DoublyHomomorphicCommitment,
+    LMC::Message: ProjectiveCurve<ScalarField = LMC::Scalar>,
+    CKP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = LMC::Key>,
+{
+    // Commitment to the commitment key held by the verifier, computed once per commitment key
+    pub fn commit_to_key(
+        ck: &[LMC::Message],
+        ck_key: &[LMC::Key],
+    ) -> Result<LMC::Output, Error> {
+        LMC::commit(ck_key, ck)
+    }
+
+    // Binds the commitment to the commitment key and the claimed final key to the challenges of the
+    // delegated argument
+    fn initial_transcript(
+        com_ck: &LMC::Output,
+        ck_final: &LMC::Message,
+        challenge: &LMC::Scalar,
+    ) -> Result<LMC::Scalar, Error> {
+        //TODO: Should use CanonicalSerialize instead of ToBytes
+        Ok(hash_to_challenge::<LMC::Scalar, D>(&to_bytes![
+            challenge, com_ck, ck_final
+        ]?))
+    }
+
+    fn key_proof_challenge(
+        last_challenge: &LMC::Scalar,
+        ck_key_final: &LMC::Key,
+    ) -> Result<LMC::Scalar, Error> {
+        //TODO: Should use CanonicalSerialize instead of ToBytes
+        Ok(hash_to_challenge::<LMC::Scalar, D>(&to_bytes![
+            last_challenge,
+            ck_key_final
+        ]?))
+    }
+}
+
+impl<LMC, CKP, D> CommitmentKeyProof for DelegatedKeyProof<LMC, CKP, D>
+where
+    D: Digest,
+    LMC: DoublyHomomorphicCommitment,
+    LMC::Message: ProjectiveCurve<ScalarField = LMC::Scalar>,
+    CKP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = LMC::Key>,
+{
+    type Scalar = LMC::Scalar;
+    type Key = LMC::Message;
+    // Commitment key, key to commit to it under LMC and prover key of the final key proof
+    type ProverKey = (Vec<LMC::Message>, Vec<LMC::Key>, CKP::ProverKey);
+    // Commitment to the commitment key and verifier key of the final key proof
+    type VerifierKey = (LMC::Output, CKP::VerifierKey);
+    type Proof = DelegatedKeyFoldingProof<LMC, CKP, D>;
+
+    fn prove(
+        pk: &Self::ProverKey,
+        transcript: &[Self::Scalar],
+        r_shift: &Self::Scalar,
+        ck_final: &Self::Key,
+        challenge: &Self::Scalar,
+    ) -> Result<Self::Proof, Error> {
+        let (ck, ck_key, ck_key_pk) = pk;
+        let coefficients = key_folding_coefficients(transcript, r_shift);
+        if ck.len() != coefficients.len() {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                ck.len(),
+                coefficients.len(),
+            )));
+        }
+        let com_ck = LMC::commit(ck_key, ck)?;
+        let initial_transcript = Self::initial_transcript(&com_ck, ck_final, challenge)?;
+
+        // Run GIPA on the commitment key and the folding coefficients
+        let (proof, aux) = <GIPA<
+            MultiexponentiationInnerProduct<LMC::Message>,
+            LMC,
+            IdentityCommitment<LMC::Scalar, LMC::Scalar>,
+            IdentityCommitment<LMC::Message, LMC::Scalar>,
+            D,
+        >>::prove_with_aux_from_transcript(
+            (ck, &coefficients),
+            (
+                ck_key,
+                &vec![HomomorphicPlaceholderValue {}; coefficients.len()],
+                &vec![HomomorphicPlaceholderValue {}],
+            ),
+            &initial_transcript,
+        )?;
+
+        // Prove final key of the commitment to the commitment key is wellformed
+        let (ck_key_final, _) = aux.ck_base;
+        let inner_transcript = aux.r_transcript;
+        let inner_transcript_inverse = inner_transcript
+            .iter()
+            .map(|x| x.inverse().unwrap())
+            .collect::<Vec<_>>();
+        let c = Self::key_proof_challenge(
+            inner_transcript.first().unwrap_or(&initial_transcript),
+            &ck_key_final,
+        )?;
+        let ck_key_proof = CKP::prove(
+            ck_key_pk,
+            &inner_transcript_inverse,
+            &<LMC::Scalar>::one(),
+            &ck_key_final,
+            &c,
+        )?;
+
+        Ok(DelegatedKeyFoldingProof {
+            gipa_proof: proof,
+            final_ck: ck_key_final,
+            final_ck_proof: ck_key_proof,
+        })
+    }
+
+    fn verify(
+        vk: &Self::VerifierKey,
+        transcript: &[Self::Scalar],
+        r_shift: &Self::Scalar,
+        ck_final: &Self::Key,
+        proof: &Self::Proof,
+        challenge: &Self::Scalar,
+    ) -> Result<bool, Error> {
+        let (com_ck, ck_key_vk) = vk;
+        if proof.gipa_proof.r_commitment_steps.len() != transcript.len() {
+            return Ok(false);
+        }
+        let initial_transcript = Self::initial_transcript(com_ck, ck_final, challenge)?;
+        // The folding coefficients are public, but are folded below in product form rather than
+        // revealed to GIPA
+        let com_t = IdentityOutput(vec![ck_final.clone()]);
+        let (base_com, inner_transcript) = GIPA::verify_recursive_challenge_transcript_from(
+            (com_ck, &IdentityOutput(Vec::new()), &com_t),
+            &proof.gipa_proof,
+            &initial_transcript,
+        )?;
+        let inner_transcript_inverse = inner_transcript
+            .iter()
+            .map(|x| x.inverse().unwrap())
+            .collect::<Vec<_>>();
+
+        // Verify final key of the commitment to the commitment key is wellformed
+        let c = Self::key_proof_challenge(
+            inner_transcript.first().unwrap_or(&initial_transcript),
+            &proof.final_ck,
+        )?;
+        let ck_key_valid = CKP::verify(
+            ck_key_vk,
+            &inner_transcript_inverse,
+            &<LMC::Scalar>::one(),
+            &proof.final_ck,
+            &proof.final_ck_proof,
+            &c,
+        )?;
+
+        // Compute final folding coefficient in product form: folding the coefficients of bit j with
+        // the inverse of the matching challenge of the delegated argument
+        let mut power_2_r = r_shift.clone();
+        let mut coefficient_base = <LMC::Scalar>::one();
+        for (x, x_inner_inv) in transcript.iter().zip(&inner_transcript_inverse) {
+            coefficient_base *= &(<LMC::Scalar>::one() + &(x.clone() * &power_2_r * x_inner_inv));
+            power_2_r *= power_2_r;
+        }
+
+        // Verify base inner product commitment
+        let (com_a, _, com_t) = base_com;
+        let a_base = vec![proof.gipa_proof.r_base.0.clone()];
+        let b_base = vec![coefficient_base];
+        let t_base = vec![MultiexponentiationInnerProduct::<LMC::Message>::inner_product(
+            &a_base, &b_base,
+        )?];
+        let base_valid = proof.gipa_proof.r_base.1 == b_base[0]
+            && LMC::verify(&vec![proof.final_ck.clone()], &a_base, &com_a)?
+            && IdentityCommitment::<LMC::Message, LMC::Scalar>::verify(
+                &vec![HomomorphicPlaceholderValue {}],
+                &t_base,
+                &com_t,
+            )?;
+
+        Ok(ck_key_valid && base_valid)
+    }
+}
+
+// GIPA with the final commitment keys proven by pluggable key proofs instead of folded by the
+// verifier, so that each instantiation can choose its own verifier cost and setup. A right message
+// known to the verifier in structured form (e.g. powers of a scalar or generators from the SRS) is
+// not committed to: it is folded like the left commitment key, so its final message is proven by RMP
+// in the same way.
+pub struct GIPAWithCKP<IP, LMC, RMC, IPC, LKP, RKP, D, RMP = CommittedMessageProof<RMC>> {
+    _inner_product: PhantomData<IP>,
+    _left_commitment: PhantomData<LMC>,
+    _right_commitment: PhantomData<RMC>,
+    _inner_product_commitment: PhantomData<IPC>,
+    _left_key_proof: PhantomData<LKP>,
+    _right_key_proof: PhantomData<RKP>,
+    _digest: PhantomData<D>,
+    _right_message_proof: PhantomData<RMP>,
+}
+
+pub struct GIPAWithCKPProof<IP, LMC, RMC, IPC, LKP, RKP, D, RMP = CommittedMessageProof<RMC>>
+where
+    D: Digest,
+    IP: InnerProduct<
+        LeftMessage = LMC::Message,
+        RightMessage = RMC::Message,
+        Output = IPC::Message,
+    >,
+    LMC: DoublyHomomorphicCommitment,
+    RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
+    IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
+    RMC::Message: MulAssign<LMC::Scalar>,
+    IPC::Message: MulAssign<LMC::Scalar>,
+    RMC::Key: MulAssign<LMC::Scalar>,
+    IPC::Key: MulAssign<LMC::Scalar>,
+    RMC::Output: MulAssign<LMC::Scalar>,
+    IPC::Output: MulAssign<LMC::Scalar>,
+    LKP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = LMC::Key>,
+    RKP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = RMC::Key>,
+    RMP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = RMC::Message>,
+{
+    gipa_proof: GIPAProof<IP, LMC, RMC, IPC, D>,
+    final_ck: (LMC::Key, RMC::Key),
+    final_ck_proof: (LKP::Proof, RKP::Proof),
+    final_message_proof: RMP::Proof,
+}
+
+impl<IP, LMC, RMC, IPC, LKP, RKP, D, RMP> Clone
+    for GIPAWithCKPProof<IP, LMC, RMC, IPC, LKP, RKP, D, RMP>
+    where
+        D: Digest,
+        IP: InnerProduct<
+            LeftMessage = LMC::Message,
+            RightMessage = RMC::Message,
+            Output = IPC::Message,
+        >,
+        LMC: DoublyHomomorphicCommitment,
+        RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
+        IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
+        RMC::Message: MulAssign<LMC::Scalar>,
+        IPC::Message: MulAssign<LMC::Scalar>,
+        RMC::Key: MulAssign<LMC::Scalar>,
+        IPC::Key: MulAssign<LMC::Scalar>,
+        RMC::Output: MulAssign<LMC::Scalar>,
+        IPC::Output: MulAssign<LMC::Scalar>,
+        LKP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = LMC::Key>,
+        RKP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = RMC::Key>,
+        RMP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = RMC::Message>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            gipa_proof: self.gipa_proof.clone(),
+            final_ck: self.final_ck.clone(),
+            final_ck_proof: self.final_ck_proof.clone(),
+            final_message_proof: self.final_message_proof.clone(),
+        }
+    }
+}
+
+impl<IP, LMC, RMC, IPC, LKP, RKP, D, RMP> GIPAWithCKPProof<IP, LMC, RMC, IPC, LKP, RKP, D, RMP>
+where
+    D: Digest,
+    IP: InnerProduct<
+        LeftMessage = LMC::Message,
+        RightMessage = RMC::Message,
+        Output = IPC::Message,
+    >,
+    LMC: DoublyHomomorphicCommitment,
+    RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
+    IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
+    RMC::Message: MulAssign<LMC::Scalar>,
+    IPC::Message: MulAssign<LMC::Scalar>,
+    RMC::Key: MulAssign<LMC::Scalar>,
+    IPC::Key: MulAssign<LMC::Scalar>,
+    RMC::Output: MulAssign<LMC::Scalar>,
+    IPC::Output: MulAssign<LMC::Scalar>,
+    LKP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = LMC::Key>,
+    RKP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = RMC::Key>,
+    RMP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = RMC::Message>,
+{
+    // The proven instance has size 2^num_rounds
+    pub(crate) fn num_rounds(&self) -> usize {
+        self.gipa_proof.r_commitment_steps.len()
+    }
+}
+
+impl<IP, LMC, RMC, IPC, LKP, RKP, D, RMP> GIPAWithCKP<IP, LMC, RMC, IPC, LKP, RKP, D, RMP>
+where
+    D: Digest,
+    IP: InnerProduct<
+        LeftMessage = LMC::Message,
+        RightMessage = RMC::Message,
+        Output = IPC::Message,
+    >,
+    LMC: DoublyHomomorphicCommitment,
+    RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
+    IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
+    RMC::Message: MulAssign<LMC::Scalar>,
+    IPC::Message: MulAssign<LMC::Scalar>,
+    RMC::Key: MulAssign<LMC::Scalar>,
+    IPC::Key: MulAssign<LMC::Scalar>,
+    RMC::Output: MulAssign<LMC::Scalar>,
+    IPC::Output: MulAssign<LMC::Scalar>,
+    LKP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = LMC::Key>,
+    RKP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = RMC::Key>,
+    RMP: CommitmentKeyProof<Scalar = LMC::Scalar, Key = RMC::Message>,
+{
+    pub fn prove(
+        values: (&[IP::LeftMessage], &[IP::RightMessage]),
+        ck: (&[LMC::Key], &[RMC::Key], &IPC::Key),
+        pk: (&LKP::ProverKey, &RKP::ProverKey, &RMP::ProverKey),
+    ) -> Result<GIPAWithCKPProof<IP, LMC, RMC, IPC, LKP, RKP, D, RMP>, Error> {
+        Self::prove_with_context(values, ck, pk, &<LMC::Scalar>::one(), &[])
+    }
+
+    // Proves a left commitment key whose folding coefficients are shifted by powers of
+    // left_key_shift, binding the context (e.g. the SRS and the shift) to the transcript
+    pub(crate) fn prove_with_context(
+        values: (&[IP::LeftMessage], &[IP::RightMessage]),
+        ck: (&[LMC::Key], &[RMC::Key], &IPC::Key),
+        pk: (&LKP::ProverKey, &RKP::ProverKey, &RMP::ProverKey),
+        left_key_shift: &LMC::Scalar,
+        context: &[u8],
+    ) -> Result<GIPAWithCKPProof<IP, LMC, RMC, IPC, LKP, RKP, D, RMP>, Error> {
+        let com = (
+            LMC::commit(ck.0, values.0)?,
+            RMC::commit(ck.1, values.1)?,
+            IPC::commit(
+                &vec![ck.2.clone()],
+                &vec![IP::inner_product(values.0, values.1)?],
+            )?,
+        );
+        let num_rounds = values.0.len().trailing_zeros() as usize;
+        let initial_transcript =
+            Self::initial_transcript(context, (&com.0, &com.1, &com.2), num_rounds)?;
+
+        // Run GIPA
+        let (proof, aux) = <GIPA<IP, LMC, RMC, IPC, D>>::prove_with_aux_from_transcript(
+            values,
+            (ck.0, ck.1, &vec![ck.2.clone()]),
+            &initial_transcript,
+        )?;
+
+        // Prove final commitment keys and final right message are wellformed
+        let (ck_a_final, ck_b_final) = aux.ck_base;
+        let transcript = aux.r_transcript;
+        let transcript_inverse = transcript
+            .iter()
+            .map(|x| x.inverse().unwrap())
+            .collect::<Vec<_>>();
+        let c = Self::key_proof_challenge(
+            transcript.first().unwrap_or(&initial_transcript),
+            &ck_a_final,
+            &ck_b_final,
+            &proof.r_base.1,
+        )?;
+        let ck_a_proof = LKP::prove(pk.0, &transcript_inverse, left_key_shift, &ck_a_final, &c)?;
+        let ck_b_proof = RKP::prove(pk.1, &transcript, &<LMC::Scalar>::one(), &ck_b_final, &c)?;
+        let m_b_proof = RMP::prove(
+            pk.2,
+            &transcript_inverse,
+            &<LMC::Scalar>::one(),
+            &proof.r_base.1,
+            &c,
+        )?;
+
+        Ok(GIPAWithCKPProof {
+            gipa_proof: proof,
+            final_ck: (ck_a_final, ck_b_final),
+            final_ck_proof: (ck_a_proof, ck_b_proof),
+            final_message_proof: m_b_proof,
+        })
+    }
+
+    pub fn verify(
+        vk: (&LKP::VerifierKey, &RKP::VerifierKey, &RMP::VerifierKey),
+        ck_t: &IPC::Key,
+        com: (&LMC::Output, &RMC::Output, &IPC::Output),
+        proof: &GIPAWithCKPProof<IP, LMC, RMC, IPC, LKP, RKP, D, RMP>,
+    ) -> Result<bool, Error> {
+        Self::verify_with_context(vk, ck_t, com, proof, &<LMC::Scalar>::one(), &[])
+    }
+
+    pub(crate) fn verify_with_context(
+        vk: (&LKP::VerifierKey, &RKP::VerifierKey, &RMP::VerifierKey),
+        ck_t: &IPC::Key,
+        com: (&LMC::Output, &RMC::Output, &IPC::Output),
+        proof: &GIPAWithCKPProof<IP, LMC, RMC, IPC, LKP, RKP, D, RMP>,
+        left_key_shift: &LMC::Scalar,
+        context: &[u8],
+    ) -> Result<bool, Error> {
+        let num_rounds = proof.num_rounds();
+        let initial_transcript = Self::initial_transcript(context, com, num_rounds)?;
+        let (base_com, transcript) = GIPA::verify_recursive_challenge_transcript_from(
+            com,
+            &proof.gipa_proof,
+            &initial_transcript,
+        )?;
+        let transcript_inverse = transcript
+            .iter()
+            .map(|x| x.inverse().unwrap())
+            .collect::<Vec<_>>();
+
+        // Verify commitment keys and final right message wellformed
+        let (ck_a_final, ck_b_final) = &proof.final_ck;
+        let (ck_a_proof, ck_b_proof) = &proof.final_ck_proof;
+        let m_b_final = &proof.gipa_proof.r_base.1;
+        let c = Self::key_proof_challenge(
+            transcript.first().unwrap_or(&initial_transcript),
+            ck_a_final,
+            ck_b_final,
+            m_b_final,
+        )?;
+        let ck_a_valid = LKP::verify(
+            vk.0,
+            &transcript_inverse,
+            left_key_shift,
+            ck_a_final,
+            ck_a_proof,
+            &c,
+        )?;
+        let ck_b_valid = RKP::verify(
+            vk.1,
+            &transcript,
+            &<LMC::Scalar>::one(),
+            ck_b_final,
+            ck_b_proof,
+            &c,
+        )?;
+        let m_b_valid = RMP::verify(
+            vk.2,
+            &transcript_inverse,
+            &<LMC::Scalar>::one(),
+            m_b_final,
+            &proof.final_message_proof,
+            &c,
+        )?;
+
+        // Verify base inner product commitment, folding public messages (under identity commitments)
+        // with the coefficients of the opposite commitment key
+        let (com_a, com_b, com_t) = base_com;
+        let a_base = vec![proof.gipa_proof.r_base.0.clone()];
+        let b_base = vec![m_b_final.clone()];
+        let t_base = vec![IP::inner_product(&a_base, &b_base)?];
+        let a_valid = match LMC::reveal(&com_a) {
+            Some(m_a) => fold_public_message(&m_a, &transcript)? == a_base[0],
+            None => LMC::verify(&vec![ck_a_final.clone()], &a_base, &com_a)?,
+        };
+        let b_valid = match RMC::reveal(&com_b) {
+            Some(m_b) => fold_public_message(&m_b, &transcript_inverse)? == b_base[0],
+            None => RMC::verify(&vec![ck_b_final.clone()], &b_base, &com_b)?,
+        };
+        let base_valid = a_valid && b_valid && IPC::verify(&vec![ck_t.clone()], &t_base, &com_t)?;
+
+        Ok(ck_a_valid && ck_b_valid && m_b_valid && base_valid)
+    }
+
+    // Binds the context, the statement and the instance size (as the number of GIPA rounds) to the
+    // first GIPA challenge
+    fn initial_transcript(
+        context: &[u8],
+        com: (&LMC::Output, &RMC::Output, &IPC::Output),
+        num_rounds: usize,
+    ) -> Result<LMC::Scalar, Error> {
+        //TODO: Should use CanonicalSerialize instead of ToBytes
+        Ok(hash_to_challenge::<LMC::Scalar, D>(&to_bytes![
+            context,
+            com.0,
+            com.1,
+            com.2,
+            num_rounds as u64
+        ]?))
+    }
+
+    // Continues the transcript from the last GIPA challenge, which is the initial transcript for
+    // instances of size 1
+    fn key_proof_challenge(
+        last_challenge: &LMC::Scalar,
+        ck_a_final: &LMC::Key,
+        ck_b_final: &RMC::Key,
+        m_b_final: &RMC::Message,
+    ) -> Result<LMC::Scalar, Error> {
+        //TODO: Should use CanonicalSerialize instead of ToBytes
+        Ok(hash_to_challenge::<LMC::Scalar, D>(&to_bytes![
+            last_challenge,
+            ck_a_final,
+            ck_b_final,
+            m_b_final
+        ]?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::{curves::PairingEngine, UniformRand};
+    use blake2::Blake2b;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::tipa::{AlphaPowers, G1Placement, KZGKeyProof, TIPA};
+    use dh_commitments::{
+        afgho16::{AFGHOCommitmentG1, AFGHOCommitmentG2},
+        pedersen::PedersenCommitment,
+        random_generators,
+    };
+    use inner_products::{ExtensionFieldElement, PairingInnerProduct};
+
+    pairing_engine_tests! {
+        type GC1 = AFGHOCommitmentG1<E>;
+        type GC2 = AFGHOCommitmentG2<E>;
+        type SC1 = PedersenCommitment<<E as PairingEngine>::G1Projective>;
+        type Fr = <E as PairingEngine>::Fr;
+        type G1 = <E as PairingEngine>::G1Projective;
+        type G2 = <E as PairingEngine>::G2Projective;
+
+        const TEST_SIZE: usize = 8;
+
+        #[test]
+        fn transparent_key_proof_test() {
+            type IP = MultiexponentiationInnerProduct<G1>;
+            type IPC = IdentityCommitment<G1, Fr>;
+            type MultiExpGIPA = GIPAWithCKP<
+                IP,
+                GC1,
+                SC1,
+                IPC,
+                TransparentKeyProof<G2, Fr>,
+                TransparentKeyProof<G1, Fr>,
+                Blake2b,
+            >;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (ck_a, ck_b, ck_t) =
+                GIPA::<IP, GC1, SC1, IPC, Blake2b>::setup(&mut rng, TEST_SIZE).unwrap();
+            let m_a = random_generators(&mut rng, TEST_SIZE);
+            let mut m_b = Vec::new();
+            for _ in 0..TEST_SIZE {
+                m_b.push(Fr::rand(&mut rng));
+            }
+            let com_a = GC1::commit(&ck_a, &m_a).unwrap();
+            let com_b = SC1::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof =
+                MultiExpGIPA::prove((&m_a, &m_b), (&ck_a, &ck_b, &ck_t), (&(), &(), &())).unwrap();
+
+            assert!(MultiExpGIPA::verify(
+                (&ck_a, &ck_b, &()),
+                &ck_t,
+                (&com_a, &com_b, &com_t),
+                &proof
+            )
+            .unwrap());
+
+            // Verifier folds a different commitment key
+            let wrong_ck_b = random_generators(&mut rng, TEST_SIZE);
+            assert!(!MultiExpGIPA::verify(
+                (&ck_a, &wrong_ck_b, &()),
+                &ck_t,
+                (&com_a, &com_b, &com_t),
+                &proof
+            )
+            .unwrap());
+        }
+
+        #[test]
+        fn delegated_key_proof_test() {
+            type IP = PairingInnerProduct<E>;
+            type IPC = IdentityCommitment<ExtensionFieldElement<E>, Fr>;
+            // Keys of the left message are folded by the prover, committed to under AFGHO with keys from
+            // the structured SRS; keys of the right message are folded by the verifier
+            type KeyFoldingProof =
+                DelegatedKeyProof<GC2, KZGKeyProof<E, G1Placement, AlphaPowers>, Blake2b>;
+            type PairingGIPA = GIPAWithCKP<
+                IP,
+                GC1,
+                GC2,
+                IPC,
+                KeyFoldingProof,
+                TransparentKeyProof<G1, Fr>,
+                Blake2b,
+            >;
+            type PairingTIPA = TIPA<IP, GC1, GC2, IPC, E, Blake2b>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (srs, ck_t) = PairingTIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let (_, ck_key) = srs.get_commitment_keys();
+            let v_srs = srs.get_verifier_key();
+            let (ck_a, ck_b, _) =
+                GIPA::<IP, GC1, GC2, IPC, Blake2b>::setup(&mut rng, TEST_SIZE).unwrap();
+            let com_ck_a = KeyFoldingProof::commit_to_key(&ck_a, &ck_key).unwrap();
+
+            let m_a = random_generators(&mut rng, TEST_SIZE);
+            let m_b = random_generators(&mut rng, TEST_SIZE);
+            let com_a = GC1::commit(&ck_a, &m_a).unwrap();
+            let com_b = GC2::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = PairingGIPA::prove(
+                (&m_a, &m_b),
+                (&ck_a, &ck_b, &ck_t),
+                (&(ck_a.clone(), ck_key.to_vec(), srs.clone()), &(), &()),
+            )
+            .unwrap();
+
+            assert!(PairingGIPA::verify(
+                (&(com_ck_a.clone(), v_srs.clone()), &ck_b, &()),
+                &ck_t,
+                (&com_a, &com_b, &com_t),
+                &proof
+            )
+            .unwrap());
+
+            // Verifier holds a commitment to a different commitment key
+            let wrong_ck_a = random_generators(&mut rng, TEST_SIZE);
+            let wrong_com_ck_a = KeyFoldingProof::commit_to_key(&wrong_ck_a, &ck_key).unwrap();
+            assert!(!PairingGIPA::verify(
+                (&(wrong_com_ck_a, v_srs.clone()), &ck_b, &()),
+                &ck_t,
+                (&com_a, &com_b, &com_t),
+                &proof
+            )
+            .unwrap());
+        }
+    }
+}
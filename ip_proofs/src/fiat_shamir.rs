@@ -0,0 +1,113 @@
+use algebra::fields::{Field, FpParameters, PrimeField};
+use digest::Digest;
+use num_traits::identities::Zero;
+
+use dh_commitments::expand_hash;
+
+// Fiat-Shamir challenges are derived by hashing the transcript to the scalar field. Full-width
+// challenges are expanded to MODULUS_BITS + CHALLENGE_SECURITY_BITS bits before reduction modulo
+// the field order, so that they are statistically close to uniform (distance at most
+// 2^-CHALLENGE_SECURITY_BITS) and no derivation is rejected. Short challenges are uniform 128-bit
+// integers; each challenge of a protocol then contributes a soundness error of about d / 2^128,
+// for d the degree of the verification equation in that challenge, instead of d / |F|.
+//
+// Challenges are also the chaining values of transcripts: each GIPA round hashes the previous
+// challenge (or the initial transcript, which is itself a challenge) together with the messages of
+// the round. With short challenges, the statement is therefore bound to the later rounds only through
+// a 128-bit value, and a prover searching for two statements with colliding initial transcripts
+// succeeds after about 2^64 hashes. Short challenges should only be used where 64-bit collision
+// resistance of the transcript is acceptable.
+pub const CHALLENGE_SECURITY_BITS: usize = 128;
+
+const CHALLENGE_DOMAIN: &[u8] = b"ripp-fiat-shamir-challenge";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChallengeWidth {
+    Short,
+    Full,
+}
+
+// Challenges are full-width unless the `short-challenges` feature is enabled
+#[cfg(not(feature = "short-challenges"))]
+pub const CHALLENGE_WIDTH: ChallengeWidth = ChallengeWidth::Full;
+#[cfg(feature = "short-challenges")]
+pub const CHALLENGE_WIDTH: ChallengeWidth = ChallengeWidth::Short;
+
+pub fn hash_to_challenge<F: PrimeField, D: Digest>(input: &[u8]) -> F {
+    hash_to_challenge_with_width::<F, D>(CHALLENGE_WIDTH, input)
+}
+
+// Challenges are nonzero, so that they can be inverted when folding
+pub fn hash_to_invertible_challenge<F: PrimeField, D: Digest>(input: &[u8]) -> (F, F) {
+    let c = hash_to_challenge::<F, D>(input);
+    let c_inv = c.inverse().unwrap();
+    (c, c_inv)
+}
+
+pub fn hash_to_challenge_with_width<F: PrimeField, D: Digest>(
+    width: ChallengeWidth,
+    input: &[u8],
+) -> F {
+    let num_bytes = match width {
+        ChallengeWidth::Short => 16,
+        ChallengeWidth::Full => {
+            (<F::Params as FpParameters>::MODULUS_BITS as usize + CHALLENGE_SECURITY_BITS + 7) / 8
+        }
+    };
+    // A zero challenge only occurs with negligible probability, in which case the next nonce is
+    // used
+    let mut counter_nonce: u64 = 0;
+    loop {
+        let mut hash_input = Vec::new();
+        hash_input.extend_from_slice(CHALLENGE_DOMAIN);
+        hash_input.extend_from_slice(&counter_nonce.to_be_bytes()[..]);
+        hash_input.extend_from_slice(input);
+        let c = reduce_bytes::<F>(&expand_hash::<D>(&hash_input, num_bytes));
+        if !c.is_zero() {
+            break c;
+        }
+        counter_nonce += 1;
+    }
+}
+
+// Interprets the bytes as a big-endian integer reduced modulo the field order
+fn reduce_bytes<F: PrimeField>(bytes: &[u8]) -> F {
+    bytes.chunks(8).fold(F::zero(), |acc, chunk| {
+        let mut limb = [0u8; 8];
+        limb[8 - chunk.len()..].copy_from_slice(chunk);
+        acc * &F::from(1u128 << (8 * chunk.len())) + &F::from(u64::from_be_bytes(limb))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::curves::PairingEngine;
+    use num_traits::identities::One;
+    use blake2::Blake2b;
+
+    pairing_engine_tests! {
+        type Fr = <E as PairingEngine>::Fr;
+
+        #[test]
+        fn reduction_test() {
+            let bytes = [1u8, 0, 0, 0, 0, 0, 0, 0, 0, 2];
+            let expected = Fr::from(2u64.pow(8)).pow(&[9]) + &Fr::from(2u64);
+            assert_eq!(reduce_bytes::<Fr>(&bytes), expected);
+        }
+
+        #[test]
+        fn challenge_width_test() {
+            let short = hash_to_challenge_with_width::<Fr, Blake2b>(ChallengeWidth::Short, b"input");
+            assert!(short.into_repr() < Fr::from(1u128 << 127).double().into_repr());
+            assert_ne!(
+                short,
+                hash_to_challenge_with_width::<Fr, Blake2b>(ChallengeWidth::Short, b"other input")
+            );
+
+            let (c, c_inv) = hash_to_invertible_challenge::<Fr, Blake2b>(b"input");
+            assert_eq!(c * &c_inv, Fr::one());
+            assert_eq!(c, hash_to_challenge::<Fr, Blake2b>(b"input"));
+        }
+    }
+}
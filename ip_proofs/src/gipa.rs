@@ -2,10 +2,16 @@ use algebra::{bytes::ToBytes, fields::Field, to_bytes};
 use digest::Digest;
 use rand::Rng;
 use num_traits::identities::One;
-use std::{marker::PhantomData, ops::MulAssign};
-
-use crate::{mul_helper, Error, InnerProductArgumentError};
-use dh_commitments::DoublyHomomorphicCommitment;
+use std::{
+    marker::PhantomData,
+    ops::{Add, MulAssign},
+};
+
+use crate::{
+    fiat_shamir::{hash_to_challenge, hash_to_invertible_challenge},
+    mul_helper, Error, InnerProductArgumentError,
+};
+use dh_commitments::{DoublyHomomorphicCommitment, TransparentSetup};
 use inner_products::InnerProduct;
 
 pub struct GIPA<IP, LMC, RMC, IPC, D> {
@@ -66,7 +72,9 @@ where
     _gipa: PhantomData<GIPA<IP, LMC, RMC, IPC, D>>,
 }
 
-//TODO: Can extend GIPA to support "identity commitments" in addition to "compact commitments", i.e. for SIPP
+// GIPA supports "identity commitments" in addition to "compact commitments" for the left and right
+// messages, i.e. for SIPP. Messages under identity commitments are public: the prover does not send
+// commitments to them and the verifier folds the messages itself.
 
 impl<IP, LMC, RMC, IPC, D> GIPA<IP, LMC, RMC, IPC, D>
 where
@@ -97,6 +105,25 @@ where
         ))
     }
 
+    // Keys are derived from the label with no trusted party, so verifiers can rederive them
+    pub fn setup_from_label(
+        label: &[u8],
+        size: usize,
+    ) -> Result<(Vec<LMC::Key>, Vec<RMC::Key>, IPC::Key), Error>
+    where
+        LMC: TransparentSetup,
+        RMC: TransparentSetup,
+        IPC: TransparentSetup,
+    {
+        Ok((
+            LMC::setup_from_label::<D>(&[label, &b"/left"[..]].concat(), size)?,
+            RMC::setup_from_label::<D>(&[label, &b"/right"[..]].concat(), size)?,
+            IPC::setup_from_label::<D>(&[label, &b"/inner_product"[..]].concat(), 1)?
+                .pop()
+                .unwrap(),
+        ))
+    }
+
     pub fn prove(
         values: (&[IP::LeftMessage], &[IP::RightMessage], &IP::Output),
         ck: (&[LMC::Key], &[RMC::Key], &IPC::Key),
@@ -119,8 +146,11 @@ where
             return Err(Box::new(InnerProductArgumentError::InnerProductInvalid));
         }
 
-        let (proof, _) =
-            Self::prove_with_aux((values.0, values.1), (ck.0, ck.1, &vec![ck.2.clone()]))?;
+        let (proof, _) = Self::prove_with_aux_from_transcript(
+            (values.0, values.1),
+            (ck.0, ck.1, &vec![ck.2.clone()]),
+            &Self::initial_transcript(com)?,
+        )?;
         Ok(proof)
     }
 
@@ -144,9 +174,12 @@ where
         )
     }
 
-    pub fn prove_with_aux(
+    // Starts the Fiat-Shamir transcript from the given value, which callers derive from the statement
+    // (e.g. public messages) to bind it to the recursive challenges
+    pub fn prove_with_aux_from_transcript(
         values: (&[IP::LeftMessage], &[IP::RightMessage]),
         ck: (&[LMC::Key], &[RMC::Key], &[IPC::Key]),
+        transcript: &LMC::Scalar,
     ) -> Result<
         (
             GIPAProof<IP, LMC, RMC, IPC, D>,
@@ -156,13 +189,18 @@ where
     > {
         let (m_a, m_b) = values;
         let (ck_a, ck_b, ck_t) = ck;
-        Self::_prove((m_a.to_vec(), m_b.to_vec()), (ck_a.to_vec(), ck_b.to_vec(), ck_t.to_vec()))
+        Self::_prove(
+            (m_a.to_vec(), m_b.to_vec()),
+            (ck_a.to_vec(), ck_b.to_vec(), ck_t.to_vec()),
+            transcript,
+        )
     }
 
     // Returns vector of recursive commitments and transcripts in reverse order
     fn _prove(
         values: (Vec<IP::LeftMessage>, Vec<IP::RightMessage>),
         ck: (Vec<LMC::Key>, Vec<RMC::Key>, Vec<IPC::Key>),
+        initial_transcript: &LMC::Scalar,
     ) -> Result<
         (
             GIPAProof<IP, LMC, RMC, IPC, D>,
@@ -194,34 +232,23 @@ where
                 let ck_b_2 = &ck_b[..split];
 
                 let com_1 = (
-                    LMC::commit(ck_a_1, m_a_1)?,
-                    RMC::commit(ck_b_1, m_b_1)?,
+                    commit_unless_identity::<LMC>(ck_a_1, m_a_1)?,
+                    commit_unless_identity::<RMC>(ck_b_1, m_b_1)?,
                     IPC::commit(&ck_t, &vec![IP::inner_product(m_a_1, m_b_1)?])?,
                 );
                 let com_2 = (
-                    LMC::commit(ck_a_2, m_a_2)?,
-                    RMC::commit(ck_b_2, m_b_2)?,
+                    commit_unless_identity::<LMC>(ck_a_2, m_a_2)?,
+                    commit_unless_identity::<RMC>(ck_b_2, m_b_2)?,
                     IPC::commit(&ck_t, &vec![IP::inner_product(m_a_2, m_b_2)?])?,
                 );
 
                 // Fiat-Shamir challenge
-                let mut counter_nonce: usize = 0;
-                let default_transcript = Default::default();
-                let transcript = r_transcript.last().unwrap_or(&default_transcript);
-                let (c, c_inv) = 'challenge: loop {
-                    let mut hash_input = Vec::new();
-                    hash_input.extend_from_slice(&counter_nonce.to_be_bytes()[..]);
-                    //TODO: Should use CanonicalSerialize instead of ToBytes
-                    hash_input.extend_from_slice(&to_bytes![
-                    transcript, com_1.0, com_1.1, com_1.2, com_2.0, com_2.1, com_2.2
-                ]?);
-                    if let Some(c) = LMC::Scalar::from_random_bytes(&D::digest(&hash_input)) {
-                        if let Some(c_inv) = c.inverse() {
-                            break 'challenge (c, c_inv);
-                        }
-                    };
-                    counter_nonce += 1;
-                };
+                let transcript = r_transcript.last().unwrap_or(initial_transcript);
+                let (c, c_inv) = Self::round_challenge(
+                    transcript,
+                    (&com_1.0, &com_1.1, &com_1.2),
+                    (&com_2.0, &com_2.1, &com_2.2),
+                )?;
 
                 // Set up values for next step of recursion
                 //TODO: Optimization: using mul_helper to individually multiply; could require a "EfficientVectorMul<Scalar>" trait on msgs/cks to make use of VariableMSM
@@ -274,40 +301,42 @@ where
     }
 
     // Helper function used to calculate recursive challenges from proof execution (transcript in reverse)
-    pub fn verify_recursive_challenge_transcript(
+    // Commitments to public messages (under identity commitments) are returned as given, to be folded
+    // by the caller using the transcript
+    pub fn verify_recursive_challenge_transcript_from(
         com: (&LMC::Output, &RMC::Output, &IPC::Output),
         proof: &GIPAProof<IP, LMC, RMC, IPC, D>,
+        transcript: &LMC::Scalar,
     ) -> Result<((LMC::Output, RMC::Output, IPC::Output), Vec<LMC::Scalar>), Error> {
-        Self::_verify_recursive_challenges((com.0.clone(), com.1.clone(), com.2.clone()), proof)
+        Self::_verify_recursive_challenges(
+            (com.0.clone(), com.1.clone(), com.2.clone()),
+            proof,
+            transcript,
+        )
     }
 
     fn _verify_recursive_challenges(
         com: (LMC::Output, RMC::Output, IPC::Output),
         proof: &GIPAProof<IP, LMC, RMC, IPC, D>,
+        initial_transcript: &LMC::Scalar,
     ) -> Result<((LMC::Output, RMC::Output, IPC::Output), Vec<LMC::Scalar>), Error> {
         let (mut com_a, mut com_b, mut com_t) = com;
         let mut r_transcript = Vec::new();
         for (com_1, com_2) in proof.r_commitment_steps.iter().rev() {
             // Fiat-Shamir challenge
-            let mut counter_nonce: usize = 0;
-            let default_transcript = Default::default();
-            let transcript = r_transcript.last().unwrap_or(&default_transcript);
-            let (c, c_inv) = 'challenge: loop {
-                let mut hash_input = Vec::new();
-                hash_input.extend_from_slice(&counter_nonce.to_be_bytes()[..]);
-                hash_input.extend_from_slice(&to_bytes![
-                transcript, com_1.0, com_1.1, com_1.2, com_2.0, com_2.1, com_2.2
-            ]?);
-                if let Some(c) = LMC::Scalar::from_random_bytes(&D::digest(&hash_input)) {
-                    if let Some(c_inv) = c.inverse() {
-                        break 'challenge (c, c_inv);
-                    }
-                };
-                counter_nonce += 1;
-            };
-
-            com_a = mul_helper(&com_1.0, &c) + com_a.clone() + mul_helper(&com_2.0, &c_inv);
-            com_b = mul_helper(&com_1.1, &c) + com_b.clone() + mul_helper(&com_2.1, &c_inv);
+            let transcript = r_transcript.last().unwrap_or(initial_transcript);
+            let (c, c_inv) = Self::round_challenge(
+                transcript,
+                (&com_1.0, &com_1.1, &com_1.2),
+                (&com_2.0, &com_2.1, &com_2.2),
+            )?;
+
+            if !LMC::is_identity() {
+                com_a = mul_helper(&com_1.0, &c) + com_a.clone() + mul_helper(&com_2.0, &c_inv);
+            }
+            if !RMC::is_identity() {
+                com_b = mul_helper(&com_1.1, &c) + com_b.clone() + mul_helper(&com_2.1, &c_inv);
+            }
             com_t = mul_helper(&com_1.2, &c) + com_t.clone() + mul_helper(&com_2.2, &c_inv);
 
             r_transcript.push(c);
@@ -321,7 +350,9 @@ where
         com: (LMC::Output, RMC::Output, IPC::Output),
         proof: &GIPAProof<IP, LMC, RMC, IPC, D>,
     ) -> Result<bool, Error> {
-        let (base_com, transcript) = Self::_verify_recursive_challenges(com, &proof)?;
+        let initial_transcript = Self::initial_transcript((&com.0, &com.1, &com.2))?;
+        let (base_com, transcript) =
+            Self::_verify_recursive_challenges(com, &proof, &initial_transcript)?;
         let (com_a, com_b, com_t) = base_com;
 
         // Calculate base commitment keys
@@ -338,22 +369,133 @@ where
             }
         }
         assert_eq!(ck_a_agg_challenge_exponents.len(), ck_a.len());
-        //TODO: Optimization: Use VariableMSM multiexponentiation
-        let ck_a_base_init = mul_helper(&ck_a[0], &ck_a_agg_challenge_exponents[0]);
-        let ck_a_base = ck_a[1..].iter().zip(&ck_a_agg_challenge_exponents[1..]).map(|(g, x)| mul_helper(g, &x))
-            .fold(ck_a_base_init, |sum, x| sum + x);
-        let ck_b_base_init = mul_helper(&ck_b[0], &ck_b_agg_challenge_exponents[0]);
-        let ck_b_base = ck_b[1..].iter().zip(&ck_b_agg_challenge_exponents[1..]).map(|(g, x)| mul_helper(g, &x))
-            .fold(ck_b_base_init, |sum, x| sum + x);
+        let ck_a_base = linear_combination(&ck_a, &ck_a_agg_challenge_exponents);
+        let ck_b_base = linear_combination(&ck_b, &ck_b_agg_challenge_exponents);
 
         let a_base = vec![proof.r_base.0.clone()];
         let b_base = vec![proof.r_base.1.clone()];
         let t_base = vec![IP::inner_product(&a_base, &b_base)?];
-        Ok(LMC::verify(&vec![ck_a_base], &a_base, &com_a)?
-            && RMC::verify(&vec![ck_b_base], &b_base, &com_b)?
-            && IPC::verify(&ck_t, &t_base, &com_t)?)
+
+        // Public messages are folded by the verifier, mirroring the folding of the opposite keys
+        let a_valid = match LMC::reveal(&com_a) {
+            Some(m_a) => {
+                if m_a.len() != ck_a.len() {
+                    return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                        m_a.len(),
+                        ck_a.len(),
+                    )));
+                }
+                linear_combination(&m_a, &ck_b_agg_challenge_exponents) == a_base[0]
+            }
+            None => LMC::verify(&vec![ck_a_base], &a_base, &com_a)?,
+        };
+        let b_valid = match RMC::reveal(&com_b) {
+            Some(m_b) => {
+                if m_b.len() != ck_b.len() {
+                    return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                        ck_b.len(),
+                        m_b.len(),
+                    )));
+                }
+                linear_combination(&m_b, &ck_a_agg_challenge_exponents) == b_base[0]
+            }
+            None => RMC::verify(&vec![ck_b_base], &b_base, &com_b)?,
+        };
+        Ok(a_valid && b_valid && IPC::verify(&ck_t, &t_base, &com_t)?)
+    }
+
+    // Challenge of a recursive step from the previous challenge and the commitments of the step, for
+    // callers that fold the messages themselves (e.g. SIPP folding affine points in parallel)
+    pub fn round_challenge(
+        transcript: &LMC::Scalar,
+        com_1: (&LMC::Output, &RMC::Output, &IPC::Output),
+        com_2: (&LMC::Output, &RMC::Output, &IPC::Output),
+    ) -> Result<(LMC::Scalar, LMC::Scalar), Error> {
+        //TODO: Should use CanonicalSerialize instead of ToBytes
+        Ok(hash_to_invertible_challenge::<LMC::Scalar, D>(&to_bytes![
+            transcript, com_1.0, com_1.1, com_1.2, com_2.0, com_2.1, com_2.2
+        ]?))
+    }
+
+    // Binds the statement to the first challenge, in particular the public messages of identity
+    // commitments which are not sent by the prover
+    fn initial_transcript(
+        com: (&LMC::Output, &RMC::Output, &IPC::Output),
+    ) -> Result<LMC::Scalar, Error> {
+        //TODO: Should use CanonicalSerialize instead of ToBytes
+        let transcript = hash_to_challenge::<LMC::Scalar, D>(&to_bytes![com.0, com.1, com.2]?);
+        Ok(transcript)
+    }
+
+}
+
+impl<IP, LMC, RMC, IPC, D> GIPAProof<IP, LMC, RMC, IPC, D>
+where
+    D: Digest,
+    IP: InnerProduct<
+        LeftMessage = LMC::Message,
+        RightMessage = RMC::Message,
+        Output = IPC::Message,
+    >,
+    LMC: DoublyHomomorphicCommitment,
+    RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
+    IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
+    RMC::Message: MulAssign<LMC::Scalar>,
+    IPC::Message: MulAssign<LMC::Scalar>,
+    RMC::Key: MulAssign<LMC::Scalar>,
+    IPC::Key: MulAssign<LMC::Scalar>,
+    RMC::Output: MulAssign<LMC::Scalar>,
+    IPC::Output: MulAssign<LMC::Scalar>,
+{
+    // Recursive commitments are stored in reverse order
+    pub fn new(
+        r_commitment_steps: Vec<(
+            (LMC::Output, RMC::Output, IPC::Output),
+            (LMC::Output, RMC::Output, IPC::Output),
+        )>,
+        r_base: (LMC::Message, RMC::Message),
+    ) -> Self {
+        GIPAProof {
+            r_commitment_steps,
+            r_base,
+            _gipa: PhantomData,
+        }
+    }
+
+    pub fn commitment_steps(
+        &self,
+    ) -> &[(
+        (LMC::Output, RMC::Output, IPC::Output),
+        (LMC::Output, RMC::Output, IPC::Output),
+    )] {
+        &self.r_commitment_steps
     }
+}
+
+// Messages under identity commitments are known to the verifier, so commitments to them are not sent
+fn commit_unless_identity<C: DoublyHomomorphicCommitment>(
+    k: &[C::Key],
+    m: &[C::Message],
+) -> Result<C::Output, Error> {
+    if C::is_identity() {
+        Ok(Default::default())
+    } else {
+        C::commit(k, m)
+    }
+}
 
+//TODO: Optimization: Use VariableMSM multiexponentiation
+pub(crate) fn linear_combination<T, F>(v: &[T], scalars: &[F]) -> T
+where
+    T: MulAssign<F> + Clone + Add<T, Output = T>,
+    F: Clone,
+{
+    let init = mul_helper(&v[0], &scalars[0]);
+    v[1..]
+        .iter()
+        .zip(&scalars[1..])
+        .map(|(x, s)| mul_helper(x, s))
+        .fold(init, |sum, x| sum + x)
 }
 
 impl<IP, LMC, RMC, IPC, D> Clone for GIPAProof<IP, LMC, RMC, IPC, D>
@@ -386,7 +528,7 @@ impl<IP, LMC, RMC, IPC, D> Clone for GIPAProof<IP, LMC, RMC, IPC, D>
 #[cfg(test)]
 mod tests {
     use super::*;
-    use algebra::{bls12_381::Bls12_381, curves::PairingEngine, UniformRand};
+    use algebra::{curves::PairingEngine, UniformRand};
     use blake2::Blake2b;
     use rand::{rngs::StdRng, SeedableRng};
 
@@ -398,105 +540,262 @@ mod tests {
     };
     use inner_products::{
         ExtensionFieldElement, InnerProduct, MultiexponentiationInnerProduct, PairingInnerProduct,
-        ScalarInnerProduct,
+        ReverseMultiexponentiationInnerProduct, ScalarInnerProduct,
     };
 
-    type GC1 = AFGHOCommitmentG1<Bls12_381>;
-    type GC2 = AFGHOCommitmentG2<Bls12_381>;
-    type SC1 = PedersenCommitment<<Bls12_381 as PairingEngine>::G1Projective>;
-    type SC2 = PedersenCommitment<<Bls12_381 as PairingEngine>::G2Projective>;
-    const TEST_SIZE: usize = 8;
-
-    #[test]
-    fn pairing_inner_product_test() {
-        type IP = PairingInnerProduct<Bls12_381>;
-        type IPC =
-            IdentityCommitment<ExtensionFieldElement<Bls12_381>, <Bls12_381 as PairingEngine>::Fr>;
-        type PairingGIPA = GIPA<IP, GC1, GC2, IPC, Blake2b>;
-
-        let mut rng = StdRng::seed_from_u64(0u64);
-        let (ck_a, ck_b, ck_t) = PairingGIPA::setup(&mut rng, TEST_SIZE).unwrap();
-        let m_a = random_generators(&mut rng, TEST_SIZE);
-        let m_b = random_generators(&mut rng, TEST_SIZE);
-        let com_a = GC1::commit(&ck_a, &m_a).unwrap();
-        let com_b = GC2::commit(&ck_b, &m_b).unwrap();
-        let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
-        let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
-
-        let proof = PairingGIPA::prove(
-            (&m_a, &m_b, &t[0]),
-            (&ck_a, &ck_b, &ck_t),
-            (&com_a, &com_b, &com_t),
-        )
-        .unwrap();
+    pairing_engine_tests! {
+        type GC1 = AFGHOCommitmentG1<E>;
+        type GC2 = AFGHOCommitmentG2<E>;
+        type SC1 = PedersenCommitment<<E as PairingEngine>::G1Projective>;
+        type SC2 = PedersenCommitment<<E as PairingEngine>::G2Projective>;
+        const TEST_SIZE: usize = 8;
+
+        #[test]
+        fn pairing_inner_product_test() {
+            type IP = PairingInnerProduct<E>;
+            type IPC =
+                IdentityCommitment<ExtensionFieldElement<E>, <E as PairingEngine>::Fr>;
+            type PairingGIPA = GIPA<IP, GC1, GC2, IPC, Blake2b>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (ck_a, ck_b, ck_t) = PairingGIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let m_a = random_generators(&mut rng, TEST_SIZE);
+            let m_b = random_generators(&mut rng, TEST_SIZE);
+            let com_a = GC1::commit(&ck_a, &m_a).unwrap();
+            let com_b = GC2::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = PairingGIPA::prove(
+                (&m_a, &m_b, &t[0]),
+                (&ck_a, &ck_b, &ck_t),
+                (&com_a, &com_b, &com_t),
+            )
+            .unwrap();
+
+            assert!(
+                PairingGIPA::verify((&ck_a, &ck_b, &ck_t), (&com_a, &com_b, &com_t), &proof,).unwrap()
+            );
+        }
 
-        assert!(
-            PairingGIPA::verify((&ck_a, &ck_b, &ck_t), (&com_a, &com_b, &com_t), &proof,).unwrap()
-        );
-    }
+        #[test]
+        fn multiexponentiation_inner_product_test() {
+            type IP = MultiexponentiationInnerProduct<<E as PairingEngine>::G1Projective>;
+            type IPC = IdentityCommitment<
+                <E as PairingEngine>::G1Projective,
+                <E as PairingEngine>::Fr,
+            >;
+            type MultiExpGIPA = GIPA<IP, GC1, SC1, IPC, Blake2b>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (ck_a, ck_b, ck_t) = MultiExpGIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let m_a = random_generators(&mut rng, TEST_SIZE);
+            let mut m_b = Vec::new();
+            for _ in 0..TEST_SIZE {
+                m_b.push(<E as PairingEngine>::Fr::rand(&mut rng));
+            }
+            let com_a = GC1::commit(&ck_a, &m_a).unwrap();
+            let com_b = SC1::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = MultiExpGIPA::prove(
+                (&m_a, &m_b, &t[0]),
+                (&ck_a, &ck_b, &ck_t),
+                (&com_a, &com_b, &com_t),
+            )
+            .unwrap();
+
+            assert!(
+                MultiExpGIPA::verify((&ck_a, &ck_b, &ck_t), (&com_a, &com_b, &com_t), &proof,).unwrap()
+            );
+        }
 
-    #[test]
-    fn multiexponentiation_inner_product_test() {
-        type IP = MultiexponentiationInnerProduct<<Bls12_381 as PairingEngine>::G1Projective>;
-        type IPC = IdentityCommitment<
-            <Bls12_381 as PairingEngine>::G1Projective,
-            <Bls12_381 as PairingEngine>::Fr,
-        >;
-        type MultiExpGIPA = GIPA<IP, GC1, SC1, IPC, Blake2b>;
-
-        let mut rng = StdRng::seed_from_u64(0u64);
-        let (ck_a, ck_b, ck_t) = MultiExpGIPA::setup(&mut rng, TEST_SIZE).unwrap();
-        let m_a = random_generators(&mut rng, TEST_SIZE);
-        let mut m_b = Vec::new();
-        for _ in 0..TEST_SIZE {
-            m_b.push(<Bls12_381 as PairingEngine>::Fr::rand(&mut rng));
+        #[test]
+        fn multiexponentiation_inner_product_transparent_setup_test() {
+            type IP = MultiexponentiationInnerProduct<<E as PairingEngine>::G1Projective>;
+            type IPC = IdentityCommitment<
+                <E as PairingEngine>::G1Projective,
+                <E as PairingEngine>::Fr,
+            >;
+            type MultiExpGIPA = GIPA<IP, GC1, SC1, IPC, Blake2b>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (ck_a, ck_b, ck_t) = MultiExpGIPA::setup_from_label(b"gipa", TEST_SIZE).unwrap();
+            let m_a = random_generators(&mut rng, TEST_SIZE);
+            let mut m_b = Vec::new();
+            for _ in 0..TEST_SIZE {
+                m_b.push(<E as PairingEngine>::Fr::rand(&mut rng));
+            }
+            let com_a = GC1::commit(&ck_a, &m_a).unwrap();
+            let com_b = SC1::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = MultiExpGIPA::prove(
+                (&m_a, &m_b, &t[0]),
+                (&ck_a, &ck_b, &ck_t),
+                (&com_a, &com_b, &com_t),
+            )
+            .unwrap();
+
+            // Verifier rederives the keys from the label
+            let (v_ck_a, v_ck_b, v_ck_t) = MultiExpGIPA::setup_from_label(b"gipa", TEST_SIZE).unwrap();
+            assert!(MultiExpGIPA::verify(
+                (&v_ck_a, &v_ck_b, &v_ck_t),
+                (&com_a, &com_b, &com_t),
+                &proof,
+            )
+            .unwrap());
         }
-        let com_a = GC1::commit(&ck_a, &m_a).unwrap();
-        let com_b = SC1::commit(&ck_b, &m_b).unwrap();
-        let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
-        let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
-
-        let proof = MultiExpGIPA::prove(
-            (&m_a, &m_b, &t[0]),
-            (&ck_a, &ck_b, &ck_t),
-            (&com_a, &com_b, &com_t),
-        )
-        .unwrap();
 
-        assert!(
-            MultiExpGIPA::verify((&ck_a, &ck_b, &ck_t), (&com_a, &com_b, &com_t), &proof,).unwrap()
-        );
-    }
+        #[test]
+        fn reverse_multiexponentiation_inner_product_test() {
+            type IP = ReverseMultiexponentiationInnerProduct<<E as PairingEngine>::G2Projective>;
+            type IPC = IdentityCommitment<
+                <E as PairingEngine>::G2Projective,
+                <E as PairingEngine>::Fr,
+            >;
+            type ReverseMultiExpGIPA = GIPA<IP, SC2, GC2, IPC, Blake2b>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (ck_a, ck_b, ck_t) = ReverseMultiExpGIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let mut m_a = Vec::new();
+            for _ in 0..TEST_SIZE {
+                m_a.push(<E as PairingEngine>::Fr::rand(&mut rng));
+            }
+            let m_b = random_generators(&mut rng, TEST_SIZE);
+            let com_a = SC2::commit(&ck_a, &m_a).unwrap();
+            let com_b = GC2::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = ReverseMultiExpGIPA::prove(
+                (&m_a, &m_b, &t[0]),
+                (&ck_a, &ck_b, &ck_t),
+                (&com_a, &com_b, &com_t),
+            )
+            .unwrap();
+
+            assert!(ReverseMultiExpGIPA::verify(
+                (&ck_a, &ck_b, &ck_t),
+                (&com_a, &com_b, &com_t),
+                &proof,
+            )
+            .unwrap());
+        }
 
-    #[test]
-    fn scalar_inner_product_test() {
-        type IP = ScalarInnerProduct<<Bls12_381 as PairingEngine>::Fr>;
-        type IPC =
-            IdentityCommitment<<Bls12_381 as PairingEngine>::Fr, <Bls12_381 as PairingEngine>::Fr>;
-        type ScalarGIPA = GIPA<IP, SC2, SC2, IPC, Blake2b>;
-
-        let mut rng = StdRng::seed_from_u64(0u64);
-        let (ck_a, ck_b, ck_t) = ScalarGIPA::setup(&mut rng, TEST_SIZE).unwrap();
-        let mut m_a = Vec::new();
-        let mut m_b = Vec::new();
-        for _ in 0..TEST_SIZE {
-            m_a.push(<Bls12_381 as PairingEngine>::Fr::rand(&mut rng));
-            m_b.push(<Bls12_381 as PairingEngine>::Fr::rand(&mut rng));
+        #[test]
+        fn multiexponentiation_inner_product_with_public_scalars_test() {
+            type IP = MultiexponentiationInnerProduct<<E as PairingEngine>::G1Projective>;
+            type SIC =
+                IdentityCommitment<<E as PairingEngine>::Fr, <E as PairingEngine>::Fr>;
+            type IPC = IdentityCommitment<
+                <E as PairingEngine>::G1Projective,
+                <E as PairingEngine>::Fr,
+            >;
+            type MultiExpGIPA = GIPA<IP, GC1, SIC, IPC, Blake2b>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (ck_a, ck_b, ck_t) = MultiExpGIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let m_a = random_generators(&mut rng, TEST_SIZE);
+            let mut m_b = Vec::new();
+            for _ in 0..TEST_SIZE {
+                m_b.push(<E as PairingEngine>::Fr::rand(&mut rng));
+            }
+            let com_a = GC1::commit(&ck_a, &m_a).unwrap();
+            let com_b = SIC::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = MultiExpGIPA::prove(
+                (&m_a, &m_b, &t[0]),
+                (&ck_a, &ck_b, &ck_t),
+                (&com_a, &com_b, &com_t),
+            )
+            .unwrap();
+
+            assert!(
+                MultiExpGIPA::verify((&ck_a, &ck_b, &ck_t), (&com_a, &com_b, &com_t), &proof,).unwrap()
+            );
+
+            let mut wrong_m_b = m_b.clone();
+            wrong_m_b[0] = <E as PairingEngine>::Fr::rand(&mut rng);
+            let wrong_com_b = SIC::commit(&ck_b, &wrong_m_b).unwrap();
+            assert!(!MultiExpGIPA::verify(
+                (&ck_a, &ck_b, &ck_t),
+                (&com_a, &wrong_com_b, &com_t),
+                &proof,
+            )
+            .unwrap());
         }
-        let com_a = SC2::commit(&ck_a, &m_a).unwrap();
-        let com_b = SC2::commit(&ck_b, &m_b).unwrap();
-        let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
-        let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
-
-        let proof = ScalarGIPA::prove(
-            (&m_a, &m_b, &t[0]),
-            (&ck_a, &ck_b, &ck_t),
-            (&com_a, &com_b, &com_t),
-        )
-        .unwrap();
 
-        assert!(
-            ScalarGIPA::verify((&ck_a, &ck_b, &ck_t), (&com_a, &com_b, &com_t), &proof,).unwrap()
-        );
+        #[test]
+        fn pairing_inner_product_with_public_messages_test() {
+            type IP = PairingInnerProduct<E>;
+            type G1IC = IdentityCommitment<
+                <E as PairingEngine>::G1Projective,
+                <E as PairingEngine>::Fr,
+            >;
+            type G2IC = IdentityCommitment<
+                <E as PairingEngine>::G2Projective,
+                <E as PairingEngine>::Fr,
+            >;
+            type IPC =
+                IdentityCommitment<ExtensionFieldElement<E>, <E as PairingEngine>::Fr>;
+            type PairingGIPA = GIPA<IP, G1IC, G2IC, IPC, Blake2b>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (ck_a, ck_b, ck_t) = PairingGIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let m_a = random_generators(&mut rng, TEST_SIZE);
+            let m_b = random_generators(&mut rng, TEST_SIZE);
+            let com_a = G1IC::commit(&ck_a, &m_a).unwrap();
+            let com_b = G2IC::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = PairingGIPA::prove(
+                (&m_a, &m_b, &t[0]),
+                (&ck_a, &ck_b, &ck_t),
+                (&com_a, &com_b, &com_t),
+            )
+            .unwrap();
+
+            assert!(
+                PairingGIPA::verify((&ck_a, &ck_b, &ck_t), (&com_a, &com_b, &com_t), &proof,).unwrap()
+            );
+        }
+
+        #[test]
+        fn scalar_inner_product_test() {
+            type IP = ScalarInnerProduct<<E as PairingEngine>::Fr>;
+            type IPC =
+                IdentityCommitment<<E as PairingEngine>::Fr, <E as PairingEngine>::Fr>;
+            type ScalarGIPA = GIPA<IP, SC2, SC2, IPC, Blake2b>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (ck_a, ck_b, ck_t) = ScalarGIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let mut m_a = Vec::new();
+            let mut m_b = Vec::new();
+            for _ in 0..TEST_SIZE {
+                m_a.push(<E as PairingEngine>::Fr::rand(&mut rng));
+                m_b.push(<E as PairingEngine>::Fr::rand(&mut rng));
+            }
+            let com_a = SC2::commit(&ck_a, &m_a).unwrap();
+            let com_b = SC2::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = ScalarGIPA::prove(
+                (&m_a, &m_b, &t[0]),
+                (&ck_a, &ck_b, &ck_t),
+                (&com_a, &com_b, &com_t),
+            )
+            .unwrap();
+
+            assert!(
+                ScalarGIPA::verify((&ck_a, &ck_b, &ck_t), (&com_a, &com_b, &com_t), &proof,).unwrap()
+            );
+        }
     }
 }
@@ -4,6 +4,12 @@ use std::{
     ops::MulAssign,
 };
 
+#[cfg(test)]
+#[macro_use]
+extern crate dh_commitments;
+
+pub mod ck_proof;
+pub mod fiat_shamir;
 pub mod gipa;
 pub mod tipa;
 pub mod applications;
@@ -1,137 +1,543 @@
 use algebra::{
     bytes::ToBytes,
-    curves::{PairingEngine, ProjectiveCurve},
+    curves::{AffineCurve, PairingEngine, ProjectiveCurve},
     fields::{Field, PrimeField},
     groups::Group,
-    msm::FixedBaseMSM,
+    msm::{FixedBaseMSM, VariableBaseMSM},
     to_bytes, UniformRand,
 };
 use digest::Digest;
-use ff_fft::polynomial::DensePolynomial;
-use itertools::Itertools;
 use num_traits::identities::{One, Zero};
 use rand::Rng;
 use std::{marker::PhantomData, ops::MulAssign};
 
 use crate::{
-    gipa::{GIPAProof, GIPA},
-    Error,
+    ck_proof::{key_folding_coefficients, CommitmentKeyProof, GIPAWithCKP, GIPAWithCKPProof},
+    Error, InnerProductArgumentError,
 };
-use dh_commitments::{
-    afgho16::{AFGHOCommitmentG1, AFGHOCommitmentG2},
-    pedersen::PedersenCommitment,
-    DoublyHomomorphicCommitment,
-};
-use inner_products::{InnerProduct, MultiexponentiationInnerProduct};
+use dh_commitments::DoublyHomomorphicCommitment;
+use inner_products::InnerProduct;
 
+pub mod structured_generators_message;
 pub mod structured_scalar_message;
 
-//TODO: Could generalize: Don't need TIPA over G1 and G2, would work with G1 and G1 or over different pairing engines
-pub trait TIPACompatibleSetup {}
+// Keys of the left message are powers of beta and keys of the right message are powers of alpha,
+// each in either group of the pairing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trapdoor {
+    Alpha,
+    Beta,
+}
+
+// Group of the pairing in which commitment keys live, which determines the SRS powers they are
+// taken from and how KZG openings of the final keys are verified
+pub trait KeyPlacement<P: PairingEngine> {
+    type Key: ProjectiveCurve<ScalarField = P::Fr>;
+
+    fn generator() -> Self::Key;
+
+    fn srs_powers(srs: &SRS<P>, trapdoor: Trapdoor) -> &SRSPowers<Self::Key>;
+
+    // Adds the powers of the trapdoor in this group to the SRS if they are not already present
+    fn setup_srs_powers(srs: &mut SRS<P>, trapdoor: Trapdoor, value: &P::Fr, num: usize);
+
+    fn verify_kzg_opening(
+        v_srs: &VerifierSRS<P>,
+        trapdoor: Trapdoor,
+        ck_final: &Self::Key,
+        ck_opening: &Self::Key,
+        transcript: &[P::Fr],
+        r_shift: &P::Fr,
+        kzg_challenge: &P::Fr,
+    ) -> bool;
+}
+
+pub struct G1Placement;
+pub struct G2Placement;
+
+impl<P: PairingEngine> KeyPlacement<P> for G1Placement {
+    type Key = P::G1Projective;
+
+    fn generator() -> Self::Key {
+        <P::G1Projective>::prime_subgroup_generator()
+    }
+
+    fn srs_powers(srs: &SRS<P>, trapdoor: Trapdoor) -> &SRSPowers<Self::Key> {
+        match trapdoor {
+            Trapdoor::Alpha => &srs.g_alpha_powers,
+            Trapdoor::Beta => &srs.g_beta_powers,
+        }
+    }
+
+    fn setup_srs_powers(srs: &mut SRS<P>, trapdoor: Trapdoor, value: &P::Fr, num: usize) {
+        let powers = match trapdoor {
+            Trapdoor::Alpha => &mut srs.g_alpha_powers,
+            Trapdoor::Beta => &mut srs.g_beta_powers,
+        };
+        if powers.is_empty() {
+            *powers = SRSPowers::new(structured_generators_scalar_power(
+                num,
+                &Self::generator(),
+                value,
+            ));
+        }
+    }
 
-impl<G: ProjectiveCurve> TIPACompatibleSetup for PedersenCommitment<G> {}
-impl<P: PairingEngine> TIPACompatibleSetup for AFGHOCommitmentG1<P> {}
-impl<P: PairingEngine> TIPACompatibleSetup for AFGHOCommitmentG2<P> {}
+    fn verify_kzg_opening(
+        v_srs: &VerifierSRS<P>,
+        trapdoor: Trapdoor,
+        ck_final: &Self::Key,
+        ck_opening: &Self::Key,
+        transcript: &[P::Fr],
+        r_shift: &P::Fr,
+        kzg_challenge: &P::Fr,
+    ) -> bool {
+        let h_trapdoor = match trapdoor {
+            Trapdoor::Alpha => &v_srs.h_alpha,
+            Trapdoor::Beta => &v_srs.h_beta,
+        };
+        let ck_polynomial_c_eval =
+            polynomial_evaluation_product_form_from_transcript(transcript, kzg_challenge, r_shift);
+        P::pairing(
+            ck_final.clone() - &v_srs.g.mul(ck_polynomial_c_eval),
+            v_srs.h.clone(),
+        ) == P::pairing(
+            ck_opening.clone(),
+            h_trapdoor.clone() - &v_srs.h.mul(kzg_challenge.clone()),
+        )
+    }
+}
+
+impl<P: PairingEngine> KeyPlacement<P> for G2Placement {
+    type Key = P::G2Projective;
+
+    fn generator() -> Self::Key {
+        <P::G2Projective>::prime_subgroup_generator()
+    }
 
-//TODO: May need to add "reverse" MultiexponentiationInnerProduct to allow for MIP with G2 messages (because TIP hard-coded G1 left and G2 right)
-pub struct TIPA<IP, LMC, RMC, IPC, P, D> {
+    fn srs_powers(srs: &SRS<P>, trapdoor: Trapdoor) -> &SRSPowers<Self::Key> {
+        match trapdoor {
+            Trapdoor::Alpha => &srs.h_alpha_powers,
+            Trapdoor::Beta => &srs.h_beta_powers,
+        }
+    }
+
+    fn setup_srs_powers(srs: &mut SRS<P>, trapdoor: Trapdoor, value: &P::Fr, num: usize) {
+        let powers = match trapdoor {
+            Trapdoor::Alpha => &mut srs.h_alpha_powers,
+            Trapdoor::Beta => &mut srs.h_beta_powers,
+        };
+        if powers.is_empty() {
+            *powers = SRSPowers::new(structured_generators_scalar_power(
+                num,
+                &Self::generator(),
+                value,
+            ));
+        }
+    }
+
+    fn verify_kzg_opening(
+        v_srs: &VerifierSRS<P>,
+        trapdoor: Trapdoor,
+        ck_final: &Self::Key,
+        ck_opening: &Self::Key,
+        transcript: &[P::Fr],
+        r_shift: &P::Fr,
+        kzg_challenge: &P::Fr,
+    ) -> bool {
+        let g_trapdoor = match trapdoor {
+            Trapdoor::Alpha => &v_srs.g_alpha,
+            Trapdoor::Beta => &v_srs.g_beta,
+        };
+        let ck_polynomial_c_eval =
+            polynomial_evaluation_product_form_from_transcript(transcript, kzg_challenge, r_shift);
+        P::pairing(
+            v_srs.g.clone(),
+            ck_final.clone() - &v_srs.h.mul(ck_polynomial_c_eval),
+        ) == P::pairing(
+            g_trapdoor.clone() - &v_srs.g.mul(kzg_challenge.clone()),
+            ck_opening.clone(),
+        )
+    }
+}
+
+// Selects the trapdoor of the SRS powers that a KZG key proof opens
+pub trait SRSTrapdoor {
+    const TRAPDOOR: Trapdoor;
+}
+
+pub struct AlphaPowers;
+pub struct BetaPowers;
+
+impl SRSTrapdoor for AlphaPowers {
+    const TRAPDOOR: Trapdoor = Trapdoor::Alpha;
+}
+
+impl SRSTrapdoor for BetaPowers {
+    const TRAPDOOR: Trapdoor = Trapdoor::Beta;
+}
+
+// The final commitment key is a KZG commitment to the polynomial of the folding coefficients
+// (interleaved with zeros) under the powers of the trapdoor, which the prover opens at the challenge
+// point and the verifier checks in O(log n) against the verifier SRS
+pub struct KZGKeyProof<P, KP, T> {
+    _pair: PhantomData<P>,
+    _key_placement: PhantomData<KP>,
+    _trapdoor: PhantomData<T>,
+}
+
+impl<P: PairingEngine, KP: KeyPlacement<P>, T: SRSTrapdoor> CommitmentKeyProof
+    for KZGKeyProof<P, KP, T>
+{
+    type Scalar = P::Fr;
+    type Key = KP::Key;
+    type ProverKey = SRS<P>;
+    type VerifierKey = VerifierSRS<P>;
+    type Proof = KP::Key;
+
+    fn prove(
+        pk: &Self::ProverKey,
+        transcript: &[P::Fr],
+        r_shift: &P::Fr,
+        _ck_final: &Self::Key,
+        challenge: &P::Fr,
+    ) -> Result<Self::Proof, Error> {
+        prove_commitment_key_kzg_opening::<KP::Key>(
+            &KP::srs_powers(pk, T::TRAPDOOR).powers,
+            transcript,
+            r_shift,
+            challenge,
+        )
+    }
+
+    fn verify(
+        vk: &Self::VerifierKey,
+        transcript: &[P::Fr],
+        r_shift: &P::Fr,
+        ck_final: &Self::Key,
+        proof: &Self::Proof,
+        challenge: &P::Fr,
+    ) -> Result<bool, Error> {
+        if !vk.supports_rounds(transcript.len()) {
+            return Ok(false);
+        }
+        Ok(KP::verify_kzg_opening(
+            vk,
+            T::TRAPDOOR,
+            ck_final,
+            proof,
+            transcript,
+            r_shift,
+            challenge,
+        ))
+    }
+}
+
+// By default keys of the left message are in G2 and keys of the right message are in G1, as for
+// the pairing inner product; other placements allow e.g. both keys in G1
+pub struct TIPA<IP, LMC, RMC, IPC, P, D, LK = G2Placement, RK = G1Placement> {
     _inner_product: PhantomData<IP>,
     _left_commitment: PhantomData<LMC>,
     _right_commitment: PhantomData<RMC>,
     _inner_product_commitment: PhantomData<IPC>,
     _pair: PhantomData<P>,
     _digest: PhantomData<D>,
+    _left_key_placement: PhantomData<LK>,
+    _right_key_placement: PhantomData<RK>,
 }
 
-pub struct TIPAProof<IP, LMC, RMC, IPC, P, D>
-where
-    D: Digest,
-    P: PairingEngine,
-    IP: InnerProduct<
-        LeftMessage = LMC::Message,
-        RightMessage = RMC::Message,
-        Output = IPC::Message,
-    >,
-    LMC: DoublyHomomorphicCommitment + TIPACompatibleSetup,
-    RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar> + TIPACompatibleSetup,
-    IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
-    RMC::Message: MulAssign<LMC::Scalar>,
-    IPC::Message: MulAssign<LMC::Scalar>,
-    RMC::Key: MulAssign<LMC::Scalar>,
-    IPC::Key: MulAssign<LMC::Scalar>,
-    RMC::Output: MulAssign<LMC::Scalar>,
-    IPC::Output: MulAssign<LMC::Scalar>,
-{
-    gipa_proof: GIPAProof<IP, LMC, RMC, IPC, D>,
-    final_ck: (LMC::Key, RMC::Key),
-    final_ck_proof: (P::G2Projective, P::G1Projective),
-    _pair: PhantomData<P>,
+// The final commitment keys are proven by KZG openings under the powers of beta (left key) and
+// alpha (right key)
+pub type TIPAProof<IP, LMC, RMC, IPC, P, D, LK = G2Placement, RK = G1Placement> = GIPAWithCKPProof<
+    IP,
+    LMC,
+    RMC,
+    IPC,
+    KZGKeyProof<P, LK, BetaPowers>,
+    KZGKeyProof<P, RK, AlphaPowers>,
+    D,
+>;
+
+// Powers of a trapdoor kept in affine form for the KZG openings, along with the commitment key of
+// every other power so that neither is recomputed for each proof
+#[derive(Clone)]
+pub struct SRSPowers<G: ProjectiveCurve> {
+    pub(crate) powers: Vec<G::Affine>,
+    pub(crate) ck: Vec<G>,
 }
 
-impl<IP, LMC, RMC, IPC, P, D> Clone for TIPAProof<IP, LMC, RMC, IPC, P, D>
-    where
-        D: Digest,
-        P: PairingEngine,
-        IP: InnerProduct<
-            LeftMessage = LMC::Message,
-            RightMessage = RMC::Message,
-            Output = IPC::Message,
-        >,
-        LMC: DoublyHomomorphicCommitment + TIPACompatibleSetup,
-        RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar> + TIPACompatibleSetup,
-        IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
-        RMC::Message: MulAssign<LMC::Scalar>,
-        IPC::Message: MulAssign<LMC::Scalar>,
-        RMC::Key: MulAssign<LMC::Scalar>,
-        IPC::Key: MulAssign<LMC::Scalar>,
-        RMC::Output: MulAssign<LMC::Scalar>,
-        IPC::Output: MulAssign<LMC::Scalar>,
-{
-    fn clone(&self) -> Self {
-        Self {
-            gipa_proof: self.gipa_proof.clone(),
-            final_ck: self.final_ck.clone(),
-            final_ck_proof: self.final_ck_proof.clone(),
-            _pair: PhantomData,
+impl<G: ProjectiveCurve> SRSPowers<G> {
+    pub fn new(powers: Vec<G>) -> Self {
+        let ck = powers.iter().step_by(2).cloned().collect();
+        SRSPowers {
+            powers: G::batch_normalization_into_affine(&powers),
+            ck,
+        }
+    }
+
+    pub fn powers(&self) -> &[G::Affine] {
+        &self.powers
+    }
+
+    pub fn len(&self) -> usize {
+        self.powers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.powers.is_empty()
+    }
+
+    // The commitment key must be every other power, as computed by new
+    fn is_consistent(&self) -> bool {
+        self.ck.len() == (self.powers.len() + 1) / 2
+            && self
+                .ck
+                .iter()
+                .zip(self.powers.iter().step_by(2))
+                .all(|(key, power)| *key == power.into_projective())
+    }
+
+    // Powers for instances of the given size; powers that are not set up stay empty
+    fn truncate(&self, size: usize) -> Result<Self, Error> {
+        if self.is_empty() {
+            return Ok(self.clone());
         }
+        if size == 0 || self.len() < 2 * size - 1 {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                self.len(),
+                size,
+            )));
+        }
+        Ok(SRSPowers {
+            powers: self.powers[..2 * size - 1].to_vec(),
+            ck: self.ck[..size].to_vec(),
+        })
     }
 }
 
+impl<G: ProjectiveCurve> Default for SRSPowers<G> {
+    fn default() -> Self {
+        SRSPowers {
+            powers: Vec::new(),
+            ck: Vec::new(),
+        }
+    }
+}
 
+// Powers of alpha and beta in the groups where commitment keys are placed; by default only
+// g^{alpha^i} and h^{beta^i} are present, and powers for other placements are empty
 #[derive(Clone)]
 pub struct SRS<P: PairingEngine> {
-    g_alpha_powers: Vec<P::G1Projective>,
-    h_beta_powers: Vec<P::G2Projective>,
-    g_beta: P::G1Projective,
-    h_alpha: P::G2Projective,
+    pub(crate) g_alpha_powers: SRSPowers<P::G1Projective>,
+    pub(crate) h_beta_powers: SRSPowers<P::G2Projective>,
+    pub(crate) g_beta_powers: SRSPowers<P::G1Projective>,
+    pub(crate) h_alpha_powers: SRSPowers<P::G2Projective>,
+    pub(crate) g_alpha: P::G1Projective,
+    pub(crate) g_beta: P::G1Projective,
+    pub(crate) h_alpha: P::G2Projective,
+    pub(crate) h_beta: P::G2Projective,
 }
 
+// Bound to the maximum instance size supported by the SRS, so that proofs for larger instances are
+// rejected
 #[derive(Clone)]
 pub struct VerifierSRS<P: PairingEngine> {
-    g: P::G1Projective,
-    h: P::G2Projective,
-    g_beta: P::G1Projective,
-    h_alpha: P::G2Projective,
+    pub(crate) g: P::G1Projective,
+    pub(crate) h: P::G2Projective,
+    pub(crate) g_alpha: P::G1Projective,
+    pub(crate) g_beta: P::G1Projective,
+    pub(crate) h_alpha: P::G2Projective,
+    pub(crate) h_beta: P::G2Projective,
+    pub(crate) max_size: usize,
+}
+
+const SRS_DIGEST_DOMAIN: &[u8] = b"ripp-tipa-srs";
+
+impl<P: PairingEngine> VerifierSRS<P> {
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    // Digest of the verifier key, which determines the powers of a well-formed SRS
+    pub fn digest<D: Digest>(&self) -> Result<Vec<u8>, Error> {
+        //TODO: Should use CanonicalSerialize instead of ToBytes
+        Ok(D::digest(&to_bytes![
+            SRS_DIGEST_DOMAIN,
+            self.g,
+            self.h,
+            self.g_alpha,
+            self.g_beta,
+            self.h_alpha,
+            self.h_beta,
+            self.max_size as u64
+        ]?)
+        .to_vec())
+    }
+
+    // Instances of size 2^num_rounds must be supported by the SRS
+    pub(crate) fn supports_rounds(&self, num_rounds: usize) -> bool {
+        (1_usize)
+            .checked_shl(num_rounds as u32)
+            .map_or(false, |size| size <= self.max_size)
+    }
 }
 
 impl<P: PairingEngine> SRS<P> {
-    pub fn get_commitment_keys(&self) -> (Vec<P::G2Projective>, Vec<P::G1Projective>) {
-        let ck_1 = self.h_beta_powers.iter().step_by(2).cloned().collect();
-        let ck_2 = self.g_alpha_powers.iter().step_by(2).cloned().collect();
-        (ck_1, ck_2)
+    fn setup<R: Rng>(rng: &mut R, size: usize) -> Self {
+        Self::setup_with_placement::<R, G2Placement, G1Placement>(rng, size)
+    }
+
+    fn setup_with_placement<R: Rng, LK: KeyPlacement<P>, RK: KeyPlacement<P>>(
+        rng: &mut R,
+        size: usize,
+    ) -> Self {
+        let alpha = <P::Fr>::rand(rng);
+        let beta = <P::Fr>::rand(rng);
+        let g = <P::G1Projective>::prime_subgroup_generator();
+        let h = <P::G2Projective>::prime_subgroup_generator();
+        // Only the powers used by the placements of the keys are computed
+        let mut srs = SRS {
+            g_alpha_powers: SRSPowers::default(),
+            h_beta_powers: SRSPowers::default(),
+            g_beta_powers: SRSPowers::default(),
+            h_alpha_powers: SRSPowers::default(),
+            g_alpha: <P::G1Projective as Group>::mul(&g, &alpha),
+            g_beta: <P::G1Projective as Group>::mul(&g, &beta),
+            h_alpha: <P::G2Projective as Group>::mul(&h, &alpha),
+            h_beta: <P::G2Projective as Group>::mul(&h, &beta),
+        };
+        LK::setup_srs_powers(&mut srs, Trapdoor::Beta, &beta, 2 * size - 1);
+        RK::setup_srs_powers(&mut srs, Trapdoor::Alpha, &alpha, 2 * size - 1);
+        srs
+    }
+
+    pub fn get_commitment_keys(&self) -> (&[P::G2Projective], &[P::G1Projective]) {
+        self.get_commitment_keys_with_placement::<G2Placement, G1Placement>()
+    }
+
+    // Keys are empty for placements the SRS was not set up for
+    pub fn get_commitment_keys_with_placement<LK: KeyPlacement<P>, RK: KeyPlacement<P>>(
+        &self,
+    ) -> (&[LK::Key], &[RK::Key]) {
+        (
+            &LK::srs_powers(self, Trapdoor::Beta).ck,
+            &RK::srs_powers(self, Trapdoor::Alpha).ck,
+        )
     }
 
     pub fn get_verifier_key(&self) -> VerifierSRS<P> {
         VerifierSRS {
-            g: self.g_alpha_powers[0].clone(),
-            h: self.h_beta_powers[0].clone(),
+            g: <P::G1Projective>::prime_subgroup_generator(),
+            h: <P::G2Projective>::prime_subgroup_generator(),
+            g_alpha: self.g_alpha.clone(),
             g_beta: self.g_beta.clone(),
             h_alpha: self.h_alpha.clone(),
+            h_beta: self.h_beta.clone(),
+            max_size: self.max_size(),
         }
     }
+
+    // Largest instance size supported by all powers the SRS was set up with
+    pub fn max_size(&self) -> usize {
+        [
+            self.g_alpha_powers.len(),
+            self.h_beta_powers.len(),
+            self.g_beta_powers.len(),
+            self.h_alpha_powers.len(),
+        ]
+        .iter()
+        .filter(|len| **len > 0)
+        .map(|len| (len + 1) / 2)
+        .min()
+        .unwrap_or(0)
+    }
+
+    // Checks that each vector of powers is (g, g^tau, g^{tau^2}, ...) for the trapdoor tau of the
+    // verifier key, that its commitment key is every other power, and that the trapdoors are
+    // consistent across both groups. Consecutive powers are checked with one pairing equation per
+    // vector by taking a random linear combination.
+    pub fn verify<R: Rng>(&self, rng: &mut R) -> Result<bool, Error> {
+        let g = <P::G1Projective>::prime_subgroup_generator();
+        let h = <P::G2Projective>::prime_subgroup_generator();
+        let mut valid = P::pairing(self.g_alpha.clone(), h.clone())
+            == P::pairing(g.clone(), self.h_alpha.clone())
+            && P::pairing(self.g_beta.clone(), h.clone())
+                == P::pairing(g.clone(), self.h_beta.clone());
+
+        for (powers, h_tau) in [
+            (&self.g_alpha_powers, &self.h_alpha),
+            (&self.g_beta_powers, &self.h_beta),
+        ]
+        .iter()
+        {
+            valid &= powers
+                .powers
+                .first()
+                .map_or(true, |first| first.into_projective() == g);
+            valid &= powers.is_consistent();
+            if let Some((next, previous)) = random_consecutive_powers(&powers.powers, rng)? {
+                valid &= P::pairing(next, h.clone()) == P::pairing(previous, (*h_tau).clone());
+            }
+        }
+        for (powers, g_tau) in [
+            (&self.h_beta_powers, &self.g_beta),
+            (&self.h_alpha_powers, &self.g_alpha),
+        ]
+        .iter()
+        {
+            valid &= powers
+                .powers
+                .first()
+                .map_or(true, |first| first.into_projective() == h);
+            valid &= powers.is_consistent();
+            if let Some((next, previous)) = random_consecutive_powers(&powers.powers, rng)? {
+                valid &= P::pairing(g.clone(), next) == P::pairing((*g_tau).clone(), previous);
+            }
+        }
+        Ok(valid)
+    }
+
+    // SRS for instances of a smaller size, keeping the placements the SRS was set up for
+    pub fn truncate(&self, size: usize) -> Result<Self, Error> {
+        Ok(SRS {
+            g_alpha_powers: self.g_alpha_powers.truncate(size)?,
+            h_beta_powers: self.h_beta_powers.truncate(size)?,
+            g_beta_powers: self.g_beta_powers.truncate(size)?,
+            h_alpha_powers: self.h_alpha_powers.truncate(size)?,
+            g_alpha: self.g_alpha.clone(),
+            g_beta: self.g_beta.clone(),
+            h_alpha: self.h_alpha.clone(),
+            h_beta: self.h_beta.clone(),
+        })
+    }
+
+    // Prover and verifier SRS for instances of a smaller size
+    pub fn specialize(&self, size: usize) -> Result<(Self, VerifierSRS<P>), Error> {
+        let srs = self.truncate(size)?;
+        let v_srs = srs.get_verifier_key();
+        Ok((srs, v_srs))
+    }
+}
+
+// Random linear combinations, with the same coefficients, of the powers after and before each
+// position; none for vectors without consecutive powers
+fn random_consecutive_powers<G: AffineCurve, R: Rng>(
+    powers: &[G],
+    rng: &mut R,
+) -> Result<Option<(G::Projective, G::Projective)>, Error> {
+    if powers.len() < 2 {
+        return Ok(None);
+    }
+    let coefficients = (0..powers.len() - 1)
+        .map(|_| <G::ScalarField>::rand(rng).into_repr())
+        .collect::<Vec<_>>();
+    Ok(Some((
+        VariableBaseMSM::multi_scalar_mul(&powers[1..], &coefficients),
+        VariableBaseMSM::multi_scalar_mul(&powers[..powers.len() - 1], &coefficients),
+    )))
 }
 
-impl<IP, LMC, RMC, IPC, P, D> TIPA<IP, LMC, RMC, IPC, P, D>
+impl<IP, LMC, RMC, IPC, P, D, LK, RK> TIPA<IP, LMC, RMC, IPC, P, D, LK, RK>
 where
     D: Digest,
     P: PairingEngine,
@@ -140,9 +546,8 @@ where
         RightMessage = RMC::Message,
         Output = IPC::Message,
     >,
-    LMC: DoublyHomomorphicCommitment<Scalar = P::Fr, Key = P::G2Projective> + TIPACompatibleSetup,
-    RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar, Key = P::G1Projective>
-        + TIPACompatibleSetup,
+    LMC: DoublyHomomorphicCommitment<Scalar = P::Fr, Key = LK::Key>,
+    RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar, Key = RK::Key>,
     IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
     LMC::Message: MulAssign<P::Fr>,
     RMC::Message: MulAssign<P::Fr>,
@@ -151,27 +556,25 @@ where
     LMC::Output: MulAssign<P::Fr>,
     RMC::Output: MulAssign<P::Fr>,
     IPC::Output: MulAssign<P::Fr>,
+    LK: KeyPlacement<P>,
+    RK: KeyPlacement<P>,
 {
     pub fn setup<R: Rng>(rng: &mut R, size: usize) -> Result<(SRS<P>, IPC::Key), Error> {
-        let alpha = <P::Fr>::rand(rng);
-        let beta = <P::Fr>::rand(rng);
-        let g = <P::G1Projective>::prime_subgroup_generator();
-        let h = <P::G2Projective>::prime_subgroup_generator();
         Ok((
-            SRS {
-                g_alpha_powers: structured_generators_scalar_power(2 * size - 1, &g, &alpha),
-                h_beta_powers: structured_generators_scalar_power(2 * size - 1, &h, &beta),
-                g_beta: <P::G1Projective as Group>::mul(&g, &beta),
-                h_alpha: <P::G2Projective as Group>::mul(&h, &alpha),
-            },
+            SRS::setup_with_placement::<R, LK, RK>(rng, size),
             IPC::setup(rng, 1)?.pop().unwrap(),
         ))
     }
+
+    pub fn get_commitment_keys(srs: &SRS<P>) -> (&[LMC::Key], &[RMC::Key]) {
+        srs.get_commitment_keys_with_placement::<LK, RK>()
+    }
+
     pub fn prove(
         srs: &SRS<P>,
         values: (&[IP::LeftMessage], &[IP::RightMessage]),
         ck: (&[LMC::Key], &[RMC::Key], &IPC::Key),
-    ) -> Result<TIPAProof<IP, LMC, RMC, IPC, P, D>, Error> {
+    ) -> Result<TIPAProof<IP, LMC, RMC, IPC, P, D, LK, RK>, Error> {
         Self::prove_with_srs_shift(srs, values, ck, &<P::Fr>::one())
     }
 
@@ -182,84 +585,38 @@ where
         values: (&[IP::LeftMessage], &[IP::RightMessage]),
         ck: (&[LMC::Key], &[RMC::Key], &IPC::Key),
         r_shift: &P::Fr,
-    ) -> Result<TIPAProof<IP, LMC, RMC, IPC, P, D>, Error> {
-        // Run GIPA
-        let (proof, aux) = <GIPA<IP, LMC, RMC, IPC, D>>::prove_with_aux(
-            values,
-            (ck.0, ck.1, &vec![ck.2.clone()]),
-        )?;
-
-        // Prove final commitment keys are wellformed
-        let (ck_a_final, ck_b_final) = aux.ck_base;
-        let transcript = aux.r_transcript;
-        let transcript_inverse = transcript.iter().map(|x| x.inverse().unwrap()).collect();
-        let r_inverse = r_shift.inverse().unwrap();
-
-        let ck_b_polynomial = DensePolynomial::from_coefficients_slice(
-            &polynomial_coefficients_from_transcript(&transcript, &<P::Fr>::one()),
-        );
-        let ck_a_polynomial = DensePolynomial::from_coefficients_slice(
-            &polynomial_coefficients_from_transcript(&transcript_inverse, &r_inverse),
-        );
-        assert_eq!(srs.g_alpha_powers.len(), ck_a_polynomial.coeffs.len());
-
-        // KZG challenge point
-        let mut counter_nonce: usize = 0;
-        let c = loop {
-            let mut hash_input = Vec::new();
-            hash_input.extend_from_slice(&counter_nonce.to_be_bytes()[..]);
-            //TODO: Should use CanonicalSerialize instead of ToBytes
-            hash_input.extend_from_slice(&to_bytes![
-                transcript.first().unwrap(),
-                ck_a_final,
-                ck_b_final
-            ]?);
-            if let Some(c) = LMC::Scalar::from_random_bytes(&D::digest(&hash_input)) {
-                break c;
-            };
-            counter_nonce += 1;
-        };
+    ) -> Result<TIPAProof<IP, LMC, RMC, IPC, P, D, LK, RK>, Error> {
+        let v_srs = srs.get_verifier_key();
+        if values.0.len() > v_srs.max_size {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                values.0.len(),
+                v_srs.max_size,
+            )));
+        }
 
-        // Complete KZG proofs
-        let ck_a_polynomial_c_eval =
-            polynomial_evaluation_product_form_from_transcript(&transcript_inverse, &c, &r_inverse);
-        let ck_b_polynomial_c_eval =
-            polynomial_evaluation_product_form_from_transcript(&transcript, &c, &<P::Fr>::one());
-
-        let quotient_polynomial_a = &(&ck_a_polynomial
-            - &DensePolynomial::from_coefficients_vec(vec![ck_a_polynomial_c_eval]))
-            / &(DensePolynomial::from_coefficients_vec(vec![-c, LMC::Scalar::one()]));
-        let quotient_polynomial_b = &(&ck_b_polynomial
-            - &DensePolynomial::from_coefficients_vec(vec![ck_b_polynomial_c_eval]))
-            / &(DensePolynomial::from_coefficients_vec(vec![-c, LMC::Scalar::one()]));
-
-        let mut quotient_polynomial_a_coeffs = quotient_polynomial_a.coeffs;
-        quotient_polynomial_a_coeffs.resize(srs.g_alpha_powers.len(), <P::Fr>::zero());
-        let mut quotient_polynomial_b_coeffs = quotient_polynomial_b.coeffs;
-        quotient_polynomial_b_coeffs.resize(srs.g_alpha_powers.len(), <P::Fr>::zero());
-
-        let quotient_a_proof = MultiexponentiationInnerProduct::inner_product(
-            &srs.h_beta_powers,
-            &quotient_polynomial_a_coeffs,
-        )?;
-        let quotient_b_proof = MultiexponentiationInnerProduct::inner_product(
-            &srs.g_alpha_powers,
-            &quotient_polynomial_b_coeffs,
-        )?;
-
-        Ok(TIPAProof {
-            gipa_proof: proof,
-            final_ck: (ck_a_final, ck_b_final),
-            final_ck_proof: (quotient_a_proof, quotient_b_proof),
-            _pair: PhantomData,
-        })
+        // Left commitment key is shifted by the inverse of r
+        <GIPAWithCKP<
+            IP,
+            LMC,
+            RMC,
+            IPC,
+            KZGKeyProof<P, LK, BetaPowers>,
+            KZGKeyProof<P, RK, AlphaPowers>,
+            D,
+        >>::prove_with_context(
+            values,
+            ck,
+            (srs, srs, &()),
+            &r_shift.inverse().unwrap(),
+            &Self::context(&v_srs, r_shift)?,
+        )
     }
 
     pub fn verify(
         v_srs: &VerifierSRS<P>,
         ck_t: &IPC::Key,
         com: (&LMC::Output, &RMC::Output, &IPC::Output),
-        proof: &TIPAProof<IP, LMC, RMC, IPC, P, D>,
+        proof: &TIPAProof<IP, LMC, RMC, IPC, P, D, LK, RK>,
     ) -> Result<bool, Error> {
         Self::verify_with_srs_shift(v_srs, ck_t, com, proof, &<P::Fr>::one())
     }
@@ -268,66 +625,69 @@ where
         v_srs: &VerifierSRS<P>,
         ck_t: &IPC::Key,
         com: (&LMC::Output, &RMC::Output, &IPC::Output),
-        proof: &TIPAProof<IP, LMC, RMC, IPC, P, D>,
+        proof: &TIPAProof<IP, LMC, RMC, IPC, P, D, LK, RK>,
         r_shift: &P::Fr,
     ) -> Result<bool, Error> {
-        let (base_com, transcript) =
-            GIPA::verify_recursive_challenge_transcript(com, &proof.gipa_proof)?;
-        let transcript_inverse = transcript.iter().map(|x| x.inverse().unwrap()).collect();
-
-        // Verify commitment keys wellformed
-        let (ck_a_final, ck_b_final) = &proof.final_ck;
-        let (ck_a_proof, ck_b_proof) = &proof.final_ck_proof;
-
-        // KZG challenge point
-        let mut counter_nonce: usize = 0;
-        let c = loop {
-            let mut hash_input = Vec::new();
-            hash_input.extend_from_slice(&counter_nonce.to_be_bytes()[..]);
-            //TODO: Should use CanonicalSerialize instead of ToBytes
-            hash_input.extend_from_slice(&to_bytes![
-                transcript.first().unwrap(),
-                ck_a_final,
-                ck_b_final
-            ]?);
-            if let Some(c) = LMC::Scalar::from_random_bytes(&D::digest(&hash_input)) {
-                break c;
-            };
-            counter_nonce += 1;
-        };
-
-        let ck_a_polynomial_c_eval = polynomial_evaluation_product_form_from_transcript(
-            &transcript_inverse,
-            &c,
+        <GIPAWithCKP<
+            IP,
+            LMC,
+            RMC,
+            IPC,
+            KZGKeyProof<P, LK, BetaPowers>,
+            KZGKeyProof<P, RK, AlphaPowers>,
+            D,
+        >>::verify_with_context(
+            (v_srs, v_srs, &()),
+            ck_t,
+            com,
+            proof,
             &r_shift.inverse().unwrap(),
-        );
-        let ck_b_polynomial_c_eval =
-            polynomial_evaluation_product_form_from_transcript(&transcript, &c, &<P::Fr>::one());
-
-        let ck_a_valid =
-            P::pairing(
-                v_srs.g.clone(),
-                ck_a_final.clone() - &v_srs.h.mul(ck_a_polynomial_c_eval),
-            ) == P::pairing(v_srs.g_beta.clone() - &v_srs.g.mul(c), ck_a_proof.clone());
-        let ck_b_valid =
-            P::pairing(
-                ck_b_final.clone() - &v_srs.g.mul(ck_b_polynomial_c_eval),
-                v_srs.h.clone(),
-            ) == P::pairing(ck_b_proof.clone(), v_srs.h_alpha.clone() - &v_srs.h.mul(c));
-
-        // Verify base inner product commitment
-        let (com_a, com_b, com_t) = base_com;
-        let a_base = vec![proof.gipa_proof.r_base.0.clone()];
-        let b_base = vec![proof.gipa_proof.r_base.1.clone()];
-        let t_base = vec![IP::inner_product(&a_base, &b_base)?];
-        let base_valid = LMC::verify(&vec![ck_a_final.clone()], &a_base, &com_a)?
-            && RMC::verify(&vec![ck_b_final.clone()], &b_base, &com_b)?
-            && IPC::verify(&vec![ck_t.clone()], &t_base, &com_t)?;
-
-        Ok(ck_a_valid && ck_b_valid && base_valid)
+            &Self::context(v_srs, r_shift)?,
+        )
+    }
+
+    // Binds the SRS and the SRS shift to the transcript
+    fn context(v_srs: &VerifierSRS<P>, r_shift: &P::Fr) -> Result<Vec<u8>, Error> {
+        let srs_digest = v_srs.digest::<D>()?;
+        //TODO: Should use CanonicalSerialize instead of ToBytes
+        Ok(to_bytes![srs_digest, r_shift]?)
     }
 }
 
+// KZG opening at the challenge point of the polynomial committed to by a final commitment key
+fn prove_commitment_key_kzg_opening<G: ProjectiveCurve>(
+    srs_powers: &[G::Affine],
+    transcript: &[G::ScalarField],
+    r_shift: &G::ScalarField,
+    kzg_challenge: &G::ScalarField,
+) -> Result<G, Error> {
+    let ck_polynomial_len = (1 << (transcript.len() + 1)) - 1;
+    if srs_powers.len() != ck_polynomial_len {
+        return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+            srs_powers.len(),
+            ck_polynomial_len,
+        )));
+    }
+
+    // Quotient coefficients of odd powers, those of even powers being the same times the challenge.
+    // The quotient (X + c) * h(X^2) has 2n - 2 nonzero coefficients, so the opening cannot be a
+    // single MSM of size n - 1 over the fixed SRS: folding c into the bases would cost n scalar
+    // multiplications per proof. The two MSMs of size n - 1 cost the same as one of size 2n - 2.
+    let quotient_coeffs =
+        quotient_coefficients_from_transcript(transcript, r_shift, kzg_challenge)
+            .iter()
+            .map(|b| b.into_repr())
+            .collect::<Vec<_>>();
+    let (even_powers, odd_powers): (Vec<_>, Vec<_>) = srs_powers[..srs_powers.len() - 1]
+        .chunks(2)
+        .map(|pair| (pair[0], pair[1]))
+        .unzip();
+
+    let mut opening = VariableBaseMSM::multi_scalar_mul(&even_powers, &quotient_coeffs);
+    opening *= kzg_challenge.clone();
+    Ok(opening + &VariableBaseMSM::multi_scalar_mul(&odd_powers, &quotient_coeffs))
+}
+
 pub fn structured_generators_scalar_power<G: ProjectiveCurve>(
     num: usize,
     g: &G,
@@ -355,7 +715,7 @@ pub fn structured_generators_scalar_power<G: ProjectiveCurve>(
 }
 
 fn polynomial_evaluation_product_form_from_transcript<F: Field>(
-    transcript: &Vec<F>,
+    transcript: &[F],
     z: &F,
     r_shift: &F,
 ) -> F {
@@ -368,28 +728,37 @@ fn polynomial_evaluation_product_form_from_transcript<F: Field>(
     product_form.iter().product()
 }
 
-fn polynomial_coefficients_from_transcript<F: Field>(transcript: &Vec<F>, r_shift: &F) -> Vec<F> {
-    let mut coefficients = vec![F::one()];
-    let mut power_2_r = r_shift.clone();
-    for (i, x) in transcript.iter().enumerate() {
-        for j in 0..(2_usize).pow(i as u32) {
-            coefficients.push(coefficients[j] * &(x.clone() * &power_2_r));
-        }
-        power_2_r *= power_2_r;
+// The commitment key polynomial is f(X) = g(X^2) for g with the folding coefficients, so writing
+// g(Y) - g(c^2) = (Y - c^2) h(Y) gives (f(X) - f(c)) / (X - c) = (X + c) h(X^2). Returns the
+// coefficients of h, found by synthetic division in O(n) without the zero-interleaved coefficients
+// of f; the quotient has coefficients c h_i at X^{2i} and h_i at X^{2i+1}.
+fn quotient_coefficients_from_transcript<F: Field>(
+    transcript: &[F],
+    r_shift: &F,
+    kzg_challenge: &F,
+) -> Vec<F> {
+    let coefficients = key_folding_coefficients(transcript, r_shift);
+    let c_square = kzg_challenge.square();
+    let mut quotient_coeffs = vec![F::zero(); coefficients.len() - 1];
+    let mut acc = F::zero();
+    for (quotient_coeff, coeff) in quotient_coeffs
+        .iter_mut()
+        .rev()
+        .zip(coefficients.iter().rev())
+    {
+        acc = acc * &c_square + coeff;
+        *quotient_coeff = acc;
     }
-    // Interleave with 0 coefficients
-    coefficients
-        .iter()
-        .interleave(vec![F::zero()].iter().cycle().take(coefficients.len() - 1))
-        .cloned()
-        .collect()
+    quotient_coeffs
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use algebra::{bls12_381::Bls12_381, curves::PairingEngine, UniformRand};
+    use algebra::{curves::PairingEngine, UniformRand};
     use blake2::Blake2b;
+    use ff_fft::polynomial::DensePolynomial;
+    use itertools::Itertools;
     use rand::{rngs::StdRng, SeedableRng};
 
     use crate::tipa::structured_scalar_message::structured_scalar_power;
@@ -401,144 +770,446 @@ mod tests {
     };
     use inner_products::{
         ExtensionFieldElement, InnerProduct, MultiexponentiationInnerProduct, PairingInnerProduct,
-        ScalarInnerProduct,
+        ReverseMultiexponentiationInnerProduct, ScalarInnerProduct,
     };
 
-    type GC1 = AFGHOCommitmentG1<Bls12_381>;
-    type GC2 = AFGHOCommitmentG2<Bls12_381>;
-    type SC1 = PedersenCommitment<<Bls12_381 as PairingEngine>::G1Projective>;
-    type SC2 = PedersenCommitment<<Bls12_381 as PairingEngine>::G2Projective>;
+    pairing_engine_tests! {
+        type GC1 = AFGHOCommitmentG1<E>;
+        type GC2 = AFGHOCommitmentG2<E>;
+        type SC1 = PedersenCommitment<<E as PairingEngine>::G1Projective>;
+        type SC2 = PedersenCommitment<<E as PairingEngine>::G2Projective>;
 
-    const TEST_SIZE: usize = 8;
+        const TEST_SIZE: usize = 8;
 
-    #[test]
-    fn pairing_inner_product_test() {
-        type IP = PairingInnerProduct<Bls12_381>;
-        type IPC =
-            IdentityCommitment<ExtensionFieldElement<Bls12_381>, <Bls12_381 as PairingEngine>::Fr>;
-        type PairingTIPA = TIPA<IP, GC1, GC2, IPC, Bls12_381, Blake2b>;
+        #[test]
+        fn pairing_inner_product_test() {
+            type IP = PairingInnerProduct<E>;
+            type IPC =
+                IdentityCommitment<ExtensionFieldElement<E>, <E as PairingEngine>::Fr>;
+            type PairingTIPA = TIPA<IP, GC1, GC2, IPC, E, Blake2b>;
 
-        let mut rng = StdRng::seed_from_u64(0u64);
-        let (srs, ck_t) = PairingTIPA::setup(&mut rng, TEST_SIZE).unwrap();
-        let (ck_a, ck_b) = srs.get_commitment_keys();
-        let v_srs = srs.get_verifier_key();
-        let m_a = random_generators(&mut rng, TEST_SIZE);
-        let m_b = random_generators(&mut rng, TEST_SIZE);
-        let com_a = GC1::commit(&ck_a, &m_a).unwrap();
-        let com_b = GC2::commit(&ck_b, &m_b).unwrap();
-        let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
-        let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
-
-        let proof = PairingTIPA::prove(&srs, (&m_a, &m_b), (&ck_a, &ck_b, &ck_t)).unwrap();
-
-        assert!(PairingTIPA::verify(&v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap());
-    }
-
-    #[test]
-    fn multiexponentiation_inner_product_test() {
-        type IP = MultiexponentiationInnerProduct<<Bls12_381 as PairingEngine>::G1Projective>;
-        type IPC = IdentityCommitment<
-            <Bls12_381 as PairingEngine>::G1Projective,
-            <Bls12_381 as PairingEngine>::Fr,
-        >;
-        type MultiExpTIPA = TIPA<IP, GC1, SC1, IPC, Bls12_381, Blake2b>;
-
-        let mut rng = StdRng::seed_from_u64(0u64);
-        let (srs, ck_t) = MultiExpTIPA::setup(&mut rng, TEST_SIZE).unwrap();
-        let (ck_a, ck_b) = srs.get_commitment_keys();
-        let v_srs = srs.get_verifier_key();
-        let m_a = random_generators(&mut rng, TEST_SIZE);
-        let mut m_b = Vec::new();
-        for _ in 0..TEST_SIZE {
-            m_b.push(<Bls12_381 as PairingEngine>::Fr::rand(&mut rng));
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (srs, ck_t) = PairingTIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let (ck_a, ck_b) = srs.get_commitment_keys();
+            let v_srs = srs.get_verifier_key();
+            let m_a = random_generators(&mut rng, TEST_SIZE);
+            let m_b = random_generators(&mut rng, TEST_SIZE);
+            let com_a = GC1::commit(&ck_a, &m_a).unwrap();
+            let com_b = GC2::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = PairingTIPA::prove(&srs, (&m_a, &m_b), (&ck_a, &ck_b, &ck_t)).unwrap();
+
+            assert!(PairingTIPA::verify(&v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap());
         }
-        let com_a = GC1::commit(&ck_a, &m_a).unwrap();
-        let com_b = SC1::commit(&ck_b, &m_b).unwrap();
-        let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
-        let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
 
-        let proof = MultiExpTIPA::prove(&srs, (&m_a, &m_b), (&ck_a, &ck_b, &ck_t)).unwrap();
+        #[test]
+        fn pairing_inner_product_size_one_test() {
+            type IP = PairingInnerProduct<E>;
+            type IPC =
+                IdentityCommitment<ExtensionFieldElement<E>, <E as PairingEngine>::Fr>;
+            type PairingTIPA = TIPA<IP, GC1, GC2, IPC, E, Blake2b>;
 
-        assert!(MultiExpTIPA::verify(&v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap());
-    }
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (srs, ck_t) = PairingTIPA::setup(&mut rng, 1).unwrap();
+            let (ck_a, ck_b) = srs.get_commitment_keys();
+            let v_srs = srs.get_verifier_key();
+            let m_a = random_generators(&mut rng, 1);
+            let m_b = random_generators(&mut rng, 1);
+            let com_a = GC1::commit(&ck_a, &m_a).unwrap();
+            let com_b = GC2::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
 
-    #[test]
-    fn scalar_inner_product_test() {
-        type IP = ScalarInnerProduct<<Bls12_381 as PairingEngine>::Fr>;
-        type IPC =
-            IdentityCommitment<<Bls12_381 as PairingEngine>::Fr, <Bls12_381 as PairingEngine>::Fr>;
-        type ScalarTIPA = TIPA<IP, SC2, SC1, IPC, Bls12_381, Blake2b>;
+            let proof = PairingTIPA::prove(&srs, (&m_a, &m_b), (&ck_a, &ck_b, &ck_t)).unwrap();
 
-        let mut rng = StdRng::seed_from_u64(0u64);
-        let (srs, ck_t) = ScalarTIPA::setup(&mut rng, TEST_SIZE).unwrap();
-        let (ck_a, ck_b) = srs.get_commitment_keys();
-        let v_srs = srs.get_verifier_key();
-        let mut m_a = Vec::new();
-        let mut m_b = Vec::new();
-        for _ in 0..TEST_SIZE {
-            m_a.push(<Bls12_381 as PairingEngine>::Fr::rand(&mut rng));
-            m_b.push(<Bls12_381 as PairingEngine>::Fr::rand(&mut rng));
+            assert!(PairingTIPA::verify(&v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap());
+
+            // The proof does not verify against a different statement
+            let wrong_com_a = GC1::commit(&ck_a, &random_generators(&mut rng, 1)).unwrap();
+            assert!(
+                !PairingTIPA::verify(&v_srs, &ck_t, (&wrong_com_a, &com_b, &com_t), &proof).unwrap()
+            );
         }
-        let com_a = SC2::commit(&ck_a, &m_a).unwrap();
-        let com_b = SC1::commit(&ck_b, &m_b).unwrap();
-        let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
-        let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
 
-        let proof = ScalarTIPA::prove(&srs, (&m_a, &m_b), (&ck_a, &ck_b, &ck_t)).unwrap();
+        #[test]
+        fn multiexponentiation_inner_product_test() {
+            type IP = MultiexponentiationInnerProduct<<E as PairingEngine>::G1Projective>;
+            type IPC = IdentityCommitment<
+                <E as PairingEngine>::G1Projective,
+                <E as PairingEngine>::Fr,
+            >;
+            type MultiExpTIPA = TIPA<IP, GC1, SC1, IPC, E, Blake2b>;
 
-        assert!(ScalarTIPA::verify(&v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap());
-    }
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (srs, ck_t) = MultiExpTIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let (ck_a, ck_b) = srs.get_commitment_keys();
+            let v_srs = srs.get_verifier_key();
+            let m_a = random_generators(&mut rng, TEST_SIZE);
+            let mut m_b = Vec::new();
+            for _ in 0..TEST_SIZE {
+                m_b.push(<E as PairingEngine>::Fr::rand(&mut rng));
+            }
+            let com_a = GC1::commit(&ck_a, &m_a).unwrap();
+            let com_b = SC1::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
 
-    #[test]
-    fn pairing_inner_product_with_srs_shift_test() {
-        type IP = PairingInnerProduct<Bls12_381>;
-        type IPC =
-            IdentityCommitment<ExtensionFieldElement<Bls12_381>, <Bls12_381 as PairingEngine>::Fr>;
-        type PairingTIPA = TIPA<IP, GC1, GC2, IPC, Bls12_381, Blake2b>;
+            let proof = MultiExpTIPA::prove(&srs, (&m_a, &m_b), (&ck_a, &ck_b, &ck_t)).unwrap();
 
-        let mut rng = StdRng::seed_from_u64(0u64);
-        let (srs, ck_t) = PairingTIPA::setup(&mut rng, TEST_SIZE).unwrap();
-        let (ck_a, ck_b) = srs.get_commitment_keys();
-        let v_srs = srs.get_verifier_key();
+            assert!(MultiExpTIPA::verify(&v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap());
+        }
 
-        let m_a = random_generators(&mut rng, TEST_SIZE);
-        let m_b = random_generators(&mut rng, TEST_SIZE);
-        let com_a = GC1::commit(&ck_a, &m_a).unwrap();
-        let com_b = GC2::commit(&ck_b, &m_b).unwrap();
+        #[test]
+        fn reverse_multiexponentiation_inner_product_test() {
+            type IP = ReverseMultiexponentiationInnerProduct<<E as PairingEngine>::G2Projective>;
+            type IPC = IdentityCommitment<
+                <E as PairingEngine>::G2Projective,
+                <E as PairingEngine>::Fr,
+            >;
+            type ReverseMultiExpTIPA = TIPA<IP, SC2, GC2, IPC, E, Blake2b>;
 
-        let r_scalar = <<Bls12_381 as PairingEngine>::Fr>::rand(&mut rng);
-        let r_vec = structured_scalar_power(TEST_SIZE, &r_scalar);
-        let m_a_r = m_a
-            .iter()
-            .zip(&r_vec)
-            .map(|(a, r)| a.mul(r))
-            .collect::<Vec<<Bls12_381 as PairingEngine>::G1Projective>>();
-        let ck_a_r = ck_a
-            .iter()
-            .zip(&r_vec)
-            .map(|(ck, r)| ck.mul(&r.inverse().unwrap()))
-            .collect::<Vec<<Bls12_381 as PairingEngine>::G2Projective>>();
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (srs, ck_t) = ReverseMultiExpTIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let (ck_a, ck_b) = srs.get_commitment_keys();
+            let v_srs = srs.get_verifier_key();
+            let mut m_a = Vec::new();
+            for _ in 0..TEST_SIZE {
+                m_a.push(<E as PairingEngine>::Fr::rand(&mut rng));
+            }
+            let m_b = random_generators(&mut rng, TEST_SIZE);
+            let com_a = SC2::commit(&ck_a, &m_a).unwrap();
+            let com_b = GC2::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
 
-        let t = vec![IP::inner_product(&m_a_r, &m_b).unwrap()];
-        let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+            let proof = ReverseMultiExpTIPA::prove(&srs, (&m_a, &m_b), (&ck_a, &ck_b, &ck_t)).unwrap();
 
-        assert_eq!(com_a, IP::inner_product(&m_a_r, &ck_a_r).unwrap());
+            assert!(
+                ReverseMultiExpTIPA::verify(&v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap()
+            );
+        }
 
-        let proof = PairingTIPA::prove_with_srs_shift(
-            &srs,
-            (&m_a_r, &m_b),
-            (&ck_a_r, &ck_b, &ck_t),
-            &r_scalar,
-        )
-        .unwrap();
-
-        assert!(PairingTIPA::verify_with_srs_shift(
-            &v_srs,
-            &ck_t,
-            (&com_a, &com_b, &com_t),
-            &proof,
-            &r_scalar
-        )
-        .unwrap());
+        #[test]
+        fn scalar_inner_product_test() {
+            type IP = ScalarInnerProduct<<E as PairingEngine>::Fr>;
+            type IPC =
+                IdentityCommitment<<E as PairingEngine>::Fr, <E as PairingEngine>::Fr>;
+            type ScalarTIPA = TIPA<IP, SC2, SC1, IPC, E, Blake2b>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (srs, ck_t) = ScalarTIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let (ck_a, ck_b) = srs.get_commitment_keys();
+            let v_srs = srs.get_verifier_key();
+            let mut m_a = Vec::new();
+            let mut m_b = Vec::new();
+            for _ in 0..TEST_SIZE {
+                m_a.push(<E as PairingEngine>::Fr::rand(&mut rng));
+                m_b.push(<E as PairingEngine>::Fr::rand(&mut rng));
+            }
+            let com_a = SC2::commit(&ck_a, &m_a).unwrap();
+            let com_b = SC1::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = ScalarTIPA::prove(&srs, (&m_a, &m_b), (&ck_a, &ck_b, &ck_t)).unwrap();
+
+            assert!(ScalarTIPA::verify(&v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap());
+        }
+
+        #[test]
+        fn multiexponentiation_inner_product_g2_test() {
+            type IP = MultiexponentiationInnerProduct<<E as PairingEngine>::G2Projective>;
+            type IPC = IdentityCommitment<
+                <E as PairingEngine>::G2Projective,
+                <E as PairingEngine>::Fr,
+            >;
+            type MultiExpTIPA =
+                TIPA<IP, GC2, SC1, IPC, E, Blake2b, G1Placement, G1Placement>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (srs, ck_t) = MultiExpTIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let (ck_a, ck_b) = MultiExpTIPA::get_commitment_keys(&srs);
+            let v_srs = srs.get_verifier_key();
+            let m_a = random_generators(&mut rng, TEST_SIZE);
+            let mut m_b = Vec::new();
+            for _ in 0..TEST_SIZE {
+                m_b.push(<E as PairingEngine>::Fr::rand(&mut rng));
+            }
+            let com_a = GC2::commit(&ck_a, &m_a).unwrap();
+            let com_b = SC1::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = MultiExpTIPA::prove(&srs, (&m_a, &m_b), (&ck_a, &ck_b, &ck_t)).unwrap();
+
+            assert!(MultiExpTIPA::verify(&v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap());
+
+            // Keys of the left message must be powers of beta rather than alpha
+            let (_, wrong_ck_a) = srs.get_commitment_keys_with_placement::<G1Placement, G1Placement>();
+            let wrong_com_a = GC2::commit(&wrong_ck_a, &m_a).unwrap();
+            let wrong_proof =
+                MultiExpTIPA::prove(&srs, (&m_a, &m_b), (&wrong_ck_a, &ck_b, &ck_t)).unwrap();
+            assert!(!MultiExpTIPA::verify(
+                &v_srs,
+                &ck_t,
+                (&wrong_com_a, &com_b, &com_t),
+                &wrong_proof
+            )
+            .unwrap());
+        }
+
+        #[test]
+        fn scalar_inner_product_g1_test() {
+            type IP = ScalarInnerProduct<<E as PairingEngine>::Fr>;
+            type IPC =
+                IdentityCommitment<<E as PairingEngine>::Fr, <E as PairingEngine>::Fr>;
+            type ScalarTIPA = TIPA<IP, SC1, SC1, IPC, E, Blake2b, G1Placement, G1Placement>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (srs, ck_t) = ScalarTIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let (ck_a, ck_b) = ScalarTIPA::get_commitment_keys(&srs);
+            let v_srs = srs.get_verifier_key();
+            let mut m_a = Vec::new();
+            let mut m_b = Vec::new();
+            for _ in 0..TEST_SIZE {
+                m_a.push(<E as PairingEngine>::Fr::rand(&mut rng));
+                m_b.push(<E as PairingEngine>::Fr::rand(&mut rng));
+            }
+            let com_a = SC1::commit(&ck_a, &m_a).unwrap();
+            let com_b = SC1::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = ScalarTIPA::prove(&srs, (&m_a, &m_b), (&ck_a, &ck_b, &ck_t)).unwrap();
+
+            assert!(ScalarTIPA::verify(&v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap());
+        }
+
+        #[test]
+        fn srs_verify_test() {
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let srs = SRS::<E>::setup(&mut rng, TEST_SIZE);
+            assert!(srs.verify(&mut rng).unwrap());
+            let srs_g1 =
+                SRS::<E>::setup_with_placement::<_, G1Placement, G2Placement>(&mut rng, TEST_SIZE);
+            assert!(srs_g1.verify(&mut rng).unwrap());
+            // Powers are only computed for the placements of the keys
+            assert!(srs_g1.g_alpha_powers.is_empty() && srs_g1.h_beta_powers.is_empty());
+            assert_eq!(srs_g1.max_size(), TEST_SIZE);
+
+            // Power not consistent with the trapdoor
+            let mut wrong_srs = srs.clone();
+            wrong_srs.g_alpha_powers.powers[3] = wrong_srs.g_alpha_powers.powers[4].clone();
+            assert!(!wrong_srs.verify(&mut rng).unwrap());
+
+            // Trapdoors not consistent across groups
+            let mut wrong_srs = srs_g1.clone();
+            wrong_srs.h_beta = wrong_srs.h_alpha.clone();
+            assert!(!wrong_srs.verify(&mut rng).unwrap());
+
+            // Powers not starting from the generator
+            let mut wrong_srs = srs.clone();
+            wrong_srs.h_beta_powers.powers.remove(0);
+            assert!(!wrong_srs.verify(&mut rng).unwrap());
+
+            // Commitment key not consistent with the powers
+            let mut wrong_srs = srs.clone();
+            wrong_srs.g_alpha_powers.ck[1] = wrong_srs.g_alpha_powers.ck[2].clone();
+            assert!(!wrong_srs.verify(&mut rng).unwrap());
+            let mut wrong_srs = srs_g1.clone();
+            wrong_srs.h_alpha_powers.ck.pop();
+            assert!(!wrong_srs.verify(&mut rng).unwrap());
+        }
+
+        // Dense polynomial of the commitment key, interleaved with zeros
+        fn polynomial_coefficients_from_transcript<F: Field>(transcript: &[F], r_shift: &F) -> Vec<F> {
+            let coefficients = key_folding_coefficients(transcript, r_shift);
+            coefficients
+                .iter()
+                .interleave(vec![F::zero()].iter().cycle().take(coefficients.len() - 1))
+                .cloned()
+                .collect()
+        }
+
+        // Quotient by (X - c) through generic dense polynomial division
+        fn dense_quotient_from_transcript<F: PrimeField>(
+            transcript: &[F],
+            r_shift: &F,
+            kzg_challenge: &F,
+        ) -> Vec<F> {
+            let ck_polynomial = DensePolynomial::from_coefficients_vec(
+                polynomial_coefficients_from_transcript(transcript, r_shift),
+            );
+            let ck_polynomial_c_eval =
+                polynomial_evaluation_product_form_from_transcript(transcript, kzg_challenge, r_shift);
+            let quotient_polynomial = &(&ck_polynomial
+                - &DensePolynomial::from_coefficients_vec(vec![ck_polynomial_c_eval]))
+                / &(DensePolynomial::from_coefficients_vec(vec![-kzg_challenge.clone(), F::one()]));
+            quotient_polynomial.coeffs
+        }
+
+        #[test]
+        fn kzg_quotient_test() {
+            type Fr = <E as PairingEngine>::Fr;
+            type G1 = <E as PairingEngine>::G1Projective;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let g = G1::prime_subgroup_generator();
+            let alpha = Fr::rand(&mut rng);
+            let r_shift = Fr::rand(&mut rng);
+            let c = Fr::rand(&mut rng);
+            for num_rounds in 0..=TEST_SIZE.trailing_zeros() as usize {
+                let transcript = (0..num_rounds)
+                    .map(|_| Fr::rand(&mut rng))
+                    .collect::<Vec<_>>();
+                let srs_powers = structured_generators_scalar_power((2 << num_rounds) - 1, &g, &alpha);
+
+                let mut dense_quotient = dense_quotient_from_transcript(&transcript, &r_shift, &c);
+                let quotient = quotient_coefficients_from_transcript(&transcript, &r_shift, &c);
+                assert_eq!(
+                    dense_quotient,
+                    quotient
+                        .iter()
+                        .flat_map(|h| vec![c * h, h.clone()])
+                        .collect::<Vec<_>>()
+                );
+
+                dense_quotient.resize(srs_powers.len(), Fr::zero());
+                assert_eq!(
+                    prove_commitment_key_kzg_opening::<G1>(
+                        &G1::batch_normalization_into_affine(&srs_powers),
+                        &transcript,
+                        &r_shift,
+                        &c,
+                    )
+                    .unwrap(),
+                    MultiexponentiationInnerProduct::inner_product(&srs_powers, &dense_quotient)
+                        .unwrap()
+                );
+            }
+        }
+
+        #[test]
+        fn srs_commitment_keys_test() {
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let srs = SRS::<E>::setup(&mut rng, TEST_SIZE);
+            let (ck_a, ck_b) = srs.get_commitment_keys();
+            assert_eq!(ck_a.len(), TEST_SIZE);
+            assert_eq!(ck_b.len(), TEST_SIZE);
+            for (i, (ck_a_i, ck_b_i)) in ck_a.iter().zip(ck_b).enumerate() {
+                assert_eq!(*ck_a_i, srs.h_beta_powers.powers[2 * i].into_projective());
+                assert_eq!(*ck_b_i, srs.g_alpha_powers.powers[2 * i].into_projective());
+            }
+
+            let truncated_srs = srs.truncate(TEST_SIZE / 2).unwrap();
+            let (truncated_ck_a, truncated_ck_b) = truncated_srs.get_commitment_keys();
+            assert_eq!(truncated_ck_a, &ck_a[..TEST_SIZE / 2]);
+            assert_eq!(truncated_ck_b, &ck_b[..TEST_SIZE / 2]);
+            assert_eq!(truncated_srs.h_beta_powers.len(), TEST_SIZE - 1);
+            assert!(truncated_srs.g_beta_powers.is_empty());
+        }
+
+        #[test]
+        fn pairing_inner_product_truncated_srs_test() {
+            type IP = PairingInnerProduct<E>;
+            type IPC =
+                IdentityCommitment<ExtensionFieldElement<E>, <E as PairingEngine>::Fr>;
+            type PairingTIPA = TIPA<IP, GC1, GC2, IPC, E, Blake2b>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (full_srs, ck_t) = PairingTIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let (srs, v_srs) = full_srs.specialize(TEST_SIZE / 2).unwrap();
+            assert_eq!(v_srs.max_size(), TEST_SIZE / 2);
+            assert!(srs.verify(&mut rng).unwrap());
+            let (ck_a, ck_b) = srs.get_commitment_keys();
+            assert_eq!(ck_a.len(), TEST_SIZE / 2);
+            let m_a = random_generators(&mut rng, TEST_SIZE / 2);
+            let m_b = random_generators(&mut rng, TEST_SIZE / 2);
+            let com_a = GC1::commit(&ck_a, &m_a).unwrap();
+            let com_b = GC2::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = PairingTIPA::prove(&srs, (&m_a, &m_b), (&ck_a, &ck_b, &ck_t)).unwrap();
+
+            assert!(PairingTIPA::verify(&v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap());
+
+            // Proof is bound to the SRS it was created with
+            let full_v_srs = full_srs.get_verifier_key();
+            assert!(
+                !PairingTIPA::verify(&full_v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap()
+            );
+
+            // Cannot truncate to a larger size
+            assert!(srs.truncate(TEST_SIZE).is_err());
+
+            // Instances larger than the SRS supports are rejected
+            let (ck_a, ck_b) = full_srs.get_commitment_keys();
+            let m_a = random_generators(&mut rng, TEST_SIZE);
+            let m_b = random_generators(&mut rng, TEST_SIZE);
+            let com_a = GC1::commit(&ck_a, &m_a).unwrap();
+            let com_b = GC2::commit(&ck_b, &m_b).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+            assert!(PairingTIPA::prove(&srs, (&m_a, &m_b), (&ck_a, &ck_b, &ck_t)).is_err());
+            let proof = PairingTIPA::prove(&full_srs, (&m_a, &m_b), (&ck_a, &ck_b, &ck_t)).unwrap();
+            assert!(PairingTIPA::verify(&full_v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap());
+            assert!(!PairingTIPA::verify(&v_srs, &ck_t, (&com_a, &com_b, &com_t), &proof).unwrap());
+        }
+
+        #[test]
+        fn pairing_inner_product_with_srs_shift_test() {
+            type IP = PairingInnerProduct<E>;
+            type IPC =
+                IdentityCommitment<ExtensionFieldElement<E>, <E as PairingEngine>::Fr>;
+            type PairingTIPA = TIPA<IP, GC1, GC2, IPC, E, Blake2b>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (srs, ck_t) = PairingTIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let (ck_a, ck_b) = srs.get_commitment_keys();
+            let v_srs = srs.get_verifier_key();
+
+            let m_a = random_generators(&mut rng, TEST_SIZE);
+            let m_b = random_generators(&mut rng, TEST_SIZE);
+            let com_a = GC1::commit(&ck_a, &m_a).unwrap();
+            let com_b = GC2::commit(&ck_b, &m_b).unwrap();
+
+            let r_scalar = <<E as PairingEngine>::Fr>::rand(&mut rng);
+            let r_vec = structured_scalar_power(TEST_SIZE, &r_scalar);
+            let m_a_r = m_a
+                .iter()
+                .zip(&r_vec)
+                .map(|(a, r)| a.mul(r))
+                .collect::<Vec<<E as PairingEngine>::G1Projective>>();
+            let ck_a_r = ck_a
+                .iter()
+                .zip(&r_vec)
+                .map(|(ck, r)| ck.mul(&r.inverse().unwrap()))
+                .collect::<Vec<<E as PairingEngine>::G2Projective>>();
+
+            let t = vec![IP::inner_product(&m_a_r, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            assert_eq!(com_a, IP::inner_product(&m_a_r, &ck_a_r).unwrap());
+
+            let proof = PairingTIPA::prove_with_srs_shift(
+                &srs,
+                (&m_a_r, &m_b),
+                (&ck_a_r, &ck_b, &ck_t),
+                &r_scalar,
+            )
+            .unwrap();
+
+            assert!(PairingTIPA::verify_with_srs_shift(
+                &v_srs,
+                &ck_t,
+                (&com_a, &com_b, &com_t),
+                &proof,
+                &r_scalar
+            )
+            .unwrap());
+        }
     }
 }
@@ -0,0 +1,202 @@
+use algebra::curves::PairingEngine;
+use digest::Digest;
+use num_traits::identities::One;
+use rand::Rng;
+use std::{marker::PhantomData, ops::MulAssign};
+
+use crate::{
+    ck_proof::{GIPAWithCKP, GIPAWithCKPProof, IdentityKeyProof},
+    tipa::{
+        structured_scalar_message::SSMPlaceholderCommitment, AlphaPowers, BetaPowers, G1Placement,
+        G2Placement, KZGKeyProof, VerifierSRS, SRS,
+    },
+    Error, InnerProductArgumentError,
+};
+use dh_commitments::{identity::HomomorphicPlaceholderValue, DoublyHomomorphicCommitment};
+use inner_products::InnerProduct;
+
+type SGMPlaceholderCommitment<P> =
+    SSMPlaceholderCommitment<<P as PairingEngine>::Fr, <P as PairingEngine>::G1Projective>;
+
+pub struct TIPAWithSGM<IP, LMC, IPC, P, D> {
+    _inner_product: PhantomData<IP>,
+    _left_commitment: PhantomData<LMC>,
+    _inner_product_commitment: PhantomData<IPC>,
+    _pair: PhantomData<P>,
+    _digest: PhantomData<D>,
+}
+
+// The final commitment key and the final message are proven by KZG openings under the powers of beta
+// and alpha respectively, as the commitment keys of TIPA
+type SGMGIPA<IP, LMC, IPC, P, D> = GIPAWithCKP<
+    IP,
+    LMC,
+    SGMPlaceholderCommitment<P>,
+    IPC,
+    KZGKeyProof<P, G2Placement, BetaPowers>,
+    IdentityKeyProof<<P as PairingEngine>::Fr>,
+    D,
+    KZGKeyProof<P, G1Placement, AlphaPowers>,
+>;
+
+pub type TIPAWithSGMProof<IP, LMC, IPC, P, D> = GIPAWithCKPProof<
+    IP,
+    LMC,
+    SGMPlaceholderCommitment<P>,
+    IPC,
+    KZGKeyProof<P, G2Placement, BetaPowers>,
+    IdentityKeyProof<<P as PairingEngine>::Fr>,
+    D,
+    KZGKeyProof<P, G1Placement, AlphaPowers>,
+>;
+
+impl<IP, LMC, IPC, P, D> TIPAWithSGM<IP, LMC, IPC, P, D>
+where
+    D: Digest,
+    P: PairingEngine,
+    IP: InnerProduct<
+        LeftMessage = LMC::Message,
+        RightMessage = P::G1Projective,
+        Output = IPC::Message,
+    >,
+    LMC: DoublyHomomorphicCommitment<Scalar = P::Fr, Key = P::G2Projective>,
+    IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
+    LMC::Message: MulAssign<P::Fr>,
+    IPC::Message: MulAssign<P::Fr>,
+    IPC::Key: MulAssign<P::Fr>,
+    LMC::Output: MulAssign<P::Fr>,
+    IPC::Output: MulAssign<P::Fr>,
+{
+    pub fn setup<R: Rng>(rng: &mut R, size: usize) -> Result<(SRS<P>, IPC::Key), Error> {
+        Ok((SRS::setup(rng, size), IPC::setup(rng, 1)?.pop().unwrap()))
+    }
+
+    // Right message must be the structured generators from the SRS (second output of get_commitment_keys)
+    pub fn prove_with_structured_generators_message(
+        srs: &SRS<P>,
+        values: (&[IP::LeftMessage], &[IP::RightMessage]),
+        ck: (&[LMC::Key], &IPC::Key),
+    ) -> Result<TIPAWithSGMProof<IP, LMC, IPC, P, D>, Error> {
+        let v_srs = srs.get_verifier_key();
+        if values.0.len() > v_srs.max_size {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                values.0.len(),
+                v_srs.max_size,
+            )));
+        }
+
+        SGMGIPA::<IP, LMC, IPC, P, D>::prove_with_context(
+            values,
+            (
+                ck.0,
+                &vec![HomomorphicPlaceholderValue {}; values.1.len()],
+                ck.1,
+            ),
+            (srs, &(), srs),
+            &<P::Fr>::one(),
+            &v_srs.digest::<D>()?,
+        )
+    }
+
+    pub fn verify_with_structured_generators_message(
+        v_srs: &VerifierSRS<P>,
+        ck_t: &IPC::Key,
+        com: (&LMC::Output, &IPC::Output),
+        proof: &TIPAWithSGMProof<IP, LMC, IPC, P, D>,
+    ) -> Result<bool, Error> {
+        SGMGIPA::<IP, LMC, IPC, P, D>::verify_with_context(
+            (v_srs, &(), v_srs),
+            ck_t,
+            (com.0, &HomomorphicPlaceholderValue {}, com.1),
+            proof,
+            &<P::Fr>::one(),
+            &v_srs.digest::<D>()?,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::{curves::PairingEngine, UniformRand};
+    use blake2::Blake2b;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use dh_commitments::{
+        identity::IdentityCommitment, pedersen::PedersenCommitment, random_generators,
+    };
+    use inner_products::{InnerProduct, ReverseMultiexponentiationInnerProduct};
+
+    pairing_engine_tests! {
+        type SC2 = PedersenCommitment<<E as PairingEngine>::G2Projective>;
+
+        const TEST_SIZE: usize = 8;
+
+        #[test]
+        fn reverse_multiexponentiation_inner_product_test() {
+            type IP =
+                ReverseMultiexponentiationInnerProduct<<E as PairingEngine>::G1Projective>;
+            type IPC = IdentityCommitment<
+                <E as PairingEngine>::G1Projective,
+                <E as PairingEngine>::Fr,
+            >;
+            type ReverseMultiExpTIPA = TIPAWithSGM<IP, SC2, IPC, E, Blake2b>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (srs, ck_t) = ReverseMultiExpTIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let (ck_a, m_b) = srs.get_commitment_keys();
+            let v_srs = srs.get_verifier_key();
+            let mut m_a = Vec::new();
+            for _ in 0..TEST_SIZE {
+                m_a.push(<E as PairingEngine>::Fr::rand(&mut rng));
+            }
+            let com_a = SC2::commit(&ck_a, &m_a).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = ReverseMultiExpTIPA::prove_with_structured_generators_message(
+                &srs,
+                (&m_a, &m_b),
+                (&ck_a, &ck_t),
+            )
+            .unwrap();
+
+            assert!(ReverseMultiExpTIPA::verify_with_structured_generators_message(
+                &v_srs,
+                &ck_t,
+                (&com_a, &com_t),
+                &proof
+            )
+            .unwrap());
+
+            // Claimed inner product not bound to the transcript
+            let wrong_com_t = IPC::commit(&vec![ck_t.clone()], &vec![t[0] + &t[0]]).unwrap();
+            assert!(!ReverseMultiExpTIPA::verify_with_structured_generators_message(
+                &v_srs,
+                &ck_t,
+                (&com_a, &wrong_com_t),
+                &proof
+            )
+            .unwrap());
+
+            // Message not taken from the SRS
+            let wrong_m_b = random_generators(&mut rng, TEST_SIZE);
+            let wrong_t = vec![IP::inner_product(&m_a, &wrong_m_b).unwrap()];
+            let wrong_com_t = IPC::commit(&vec![ck_t.clone()], &wrong_t).unwrap();
+            let wrong_proof = ReverseMultiExpTIPA::prove_with_structured_generators_message(
+                &srs,
+                (&m_a, &wrong_m_b),
+                (&ck_a, &ck_t),
+            )
+            .unwrap();
+
+            assert!(!ReverseMultiExpTIPA::verify_with_structured_generators_message(
+                &v_srs,
+                &ck_t,
+                (&com_a, &wrong_com_t),
+                &wrong_proof
+            )
+            .unwrap());
+        }
+    }
+}
@@ -1,102 +1,169 @@
-use algebra::{curves::PairingEngine, fields::Field};
+use algebra::{bytes::ToBytes, curves::PairingEngine, fields::{Field, PrimeField}, to_bytes};
 use digest::Digest;
 use num_traits::identities::One;
-use std::ops::MulAssign;
+use rand::Rng;
+use std::{
+    marker::PhantomData,
+    ops::{Add, MulAssign},
+};
 
 use crate::{
-    gipa::GIPA,
-    tipa::{TIPACompatibleSetup, TIPAProof, VerifierSRS, SRS, TIPA},
-    Error,
+    ck_proof::{CommitmentKeyProof, GIPAWithCKP, GIPAWithCKPProof, IdentityKeyProof},
+    tipa::{
+        polynomial_evaluation_product_form_from_transcript, BetaPowers, G2Placement, KZGKeyProof,
+        VerifierSRS, SRS,
+    },
+    Error, InnerProductArgumentError,
 };
-use dh_commitments::DoublyHomomorphicCommitment;
+use dh_commitments::{identity::HomomorphicPlaceholderValue, DoublyHomomorphicCommitment};
 use inner_products::InnerProduct;
 
-//TODO: Properly generalize the non-committed message approach of SIPP and MIPP to GIPA
 //TODO: Structured message is a special case of the non-committed message and does not rely on TIPA
-//TODO: Can support structured group element messages as well as structured scalar messages
 
-// TODO: Currently implemented by completely reusing TIPA, which means wasted prover and verifier effort on B
+// The right message is known to the verifier, e.g. (1, b, b^2, ...) from b alone or structured
+// generators from the SRS, so it is never committed to: GIPA runs with a placeholder commitment on the
+// right and the verifier computes the final message from the transcript.
+#[derive(Clone)]
+pub struct SSMPlaceholderCommitment<F, M = F> {
+    _scalar: PhantomData<F>,
+    _message: PhantomData<M>,
+}
 
-pub struct TIPAWithSSMProof<IP, LMC, RMC, IPC, P, D>
+impl<F, M> DoublyHomomorphicCommitment for SSMPlaceholderCommitment<F, M>
 where
-    D: Digest,
-    P: PairingEngine,
-    IP: InnerProduct<
-        LeftMessage = LMC::Message,
-        RightMessage = RMC::Message,
-        Output = IPC::Message,
-    >,
-    LMC: DoublyHomomorphicCommitment + TIPACompatibleSetup,
-    RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar, Message = P::Fr> + TIPACompatibleSetup,
-    IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
-    RMC::Message: MulAssign<LMC::Scalar>,
-    IPC::Message: MulAssign<LMC::Scalar>,
-    RMC::Key: MulAssign<LMC::Scalar>,
-    IPC::Key: MulAssign<LMC::Scalar>,
-    RMC::Output: MulAssign<LMC::Scalar>,
-    IPC::Output: MulAssign<LMC::Scalar>,
+    F: PrimeField,
+    M: ToBytes + Clone + Default + Eq + Add<M, Output = M> + MulAssign<F>,
 {
-    tipa_proof: TIPAProof<IP, LMC, RMC, IPC, P, D>,
-    com_b: RMC::Output, //TODO: Needed because reusing TIPA
+    type Scalar = F;
+    type Message = M;
+    type Key = HomomorphicPlaceholderValue;
+    type Output = HomomorphicPlaceholderValue;
+
+    fn setup<R: Rng>(_rng: &mut R, size: usize) -> Result<Vec<Self::Key>, Error> {
+        Ok(vec![HomomorphicPlaceholderValue {}; size])
+    }
+
+    fn commit(_k: &[Self::Key], _m: &[Self::Message]) -> Result<Self::Output, Error> {
+        Ok(HomomorphicPlaceholderValue {})
+    }
 }
 
-impl<IP, LMC, RMC, IPC, P, D> Clone for TIPAWithSSMProof<IP, LMC, RMC, IPC, P, D>
-    where
-        D: Digest,
-        P: PairingEngine,
-        IP: InnerProduct<
-            LeftMessage = LMC::Message,
-            RightMessage = RMC::Message,
-            Output = IPC::Message,
-        >,
-        LMC: DoublyHomomorphicCommitment + TIPACompatibleSetup,
-        RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar, Message = P::Fr> + TIPACompatibleSetup,
-        IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
-        RMC::Message: MulAssign<LMC::Scalar>,
-        IPC::Message: MulAssign<LMC::Scalar>,
-        RMC::Key: MulAssign<LMC::Scalar>,
-        IPC::Key: MulAssign<LMC::Scalar>,
-        RMC::Output: MulAssign<LMC::Scalar>,
-        IPC::Output: MulAssign<LMC::Scalar>,
-{
-    fn clone(&self) -> Self {
-        Self {
-            tipa_proof: self.tipa_proof.clone(),
-            com_b: self.com_b.clone(),
-        }
+// The final message of the structured scalar message (1, b, b^2, ...) is computed by the verifier
+// from b in product form, so there is nothing to prove about it
+pub struct StructuredScalarProof<F> {
+    _scalar: PhantomData<F>,
+}
+
+impl<F: PrimeField> CommitmentKeyProof for StructuredScalarProof<F> {
+    type Scalar = F;
+    type Key = F;
+    type ProverKey = ();
+    type VerifierKey = F;
+    type Proof = ();
+
+    fn prove(
+        _pk: &Self::ProverKey,
+        _transcript: &[F],
+        _r_shift: &F,
+        _ck_final: &Self::Key,
+        _challenge: &F,
+    ) -> Result<Self::Proof, Error> {
+        Ok(())
+    }
+
+    fn verify(
+        vk: &Self::VerifierKey,
+        transcript: &[F],
+        r_shift: &F,
+        ck_final: &Self::Key,
+        _proof: &Self::Proof,
+        _challenge: &F,
+    ) -> Result<bool, Error> {
+        Ok(polynomial_evaluation_product_form_from_transcript(
+            transcript,
+            &F::one(),
+            &(r_shift.clone() * vk),
+        ) == *ck_final)
     }
 }
 
-impl<IP, LMC, RMC, IPC, P, D> TIPA<IP, LMC, RMC, IPC, P, D>
+pub struct TIPAWithSSM<IP, LMC, IPC, P, D> {
+    _inner_product: PhantomData<IP>,
+    _left_commitment: PhantomData<LMC>,
+    _inner_product_commitment: PhantomData<IPC>,
+    _pair: PhantomData<P>,
+    _digest: PhantomData<D>,
+}
+
+// The final commitment key is proven by a KZG opening under the powers of beta, as for TIPA
+type SSMGIPA<IP, LMC, IPC, P, D> = GIPAWithCKP<
+    IP,
+    LMC,
+    SSMPlaceholderCommitment<<P as PairingEngine>::Fr>,
+    IPC,
+    KZGKeyProof<P, G2Placement, BetaPowers>,
+    IdentityKeyProof<<P as PairingEngine>::Fr>,
+    D,
+    StructuredScalarProof<<P as PairingEngine>::Fr>,
+>;
+
+pub type TIPAWithSSMProof<IP, LMC, IPC, P, D> = GIPAWithCKPProof<
+    IP,
+    LMC,
+    SSMPlaceholderCommitment<<P as PairingEngine>::Fr>,
+    IPC,
+    KZGKeyProof<P, G2Placement, BetaPowers>,
+    IdentityKeyProof<<P as PairingEngine>::Fr>,
+    D,
+    StructuredScalarProof<<P as PairingEngine>::Fr>,
+>;
+
+impl<IP, LMC, IPC, P, D> TIPAWithSSM<IP, LMC, IPC, P, D>
 where
     D: Digest,
     P: PairingEngine,
     IP: InnerProduct<
         LeftMessage = LMC::Message,
-        RightMessage = RMC::Message,
+        RightMessage = LMC::Scalar,
         Output = IPC::Message,
     >,
-    LMC: DoublyHomomorphicCommitment<Scalar = P::Fr, Key = P::G2Projective> + TIPACompatibleSetup,
-    RMC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar, Key = P::G1Projective, Message = P::Fr>
-        + TIPACompatibleSetup,
+    LMC: DoublyHomomorphicCommitment<Scalar = P::Fr, Key = P::G2Projective>,
     IPC: DoublyHomomorphicCommitment<Scalar = LMC::Scalar>,
     LMC::Message: MulAssign<P::Fr>,
-    RMC::Message: MulAssign<P::Fr>,
     IPC::Message: MulAssign<P::Fr>,
     IPC::Key: MulAssign<P::Fr>,
     LMC::Output: MulAssign<P::Fr>,
-    RMC::Output: MulAssign<P::Fr>,
     IPC::Output: MulAssign<P::Fr>,
 {
+    pub fn setup<R: Rng>(rng: &mut R, size: usize) -> Result<(SRS<P>, IPC::Key), Error> {
+        Ok((SRS::setup(rng, size), IPC::setup(rng, 1)?.pop().unwrap()))
+    }
+
     pub fn prove_with_structured_scalar_message(
         srs: &SRS<P>,
         values: (&[IP::LeftMessage], &[IP::RightMessage]),
-        ck: (&[LMC::Key], &[RMC::Key], &IPC::Key),
-    ) -> Result<TIPAWithSSMProof<IP, LMC, RMC, IPC, P, D>, Error> {
-        Ok(TIPAWithSSMProof {
-            tipa_proof: TIPA::prove(srs, values, ck)?,
-            com_b: RMC::commit(ck.1, values.1)?,
-        })
+        ck: (&[LMC::Key], &IPC::Key),
+        scalar_b: &P::Fr,
+    ) -> Result<TIPAWithSSMProof<IP, LMC, IPC, P, D>, Error> {
+        let v_srs = srs.get_verifier_key();
+        if values.0.len() > v_srs.max_size {
+            return Err(Box::new(InnerProductArgumentError::MessageLengthInvalid(
+                values.0.len(),
+                v_srs.max_size,
+            )));
+        }
+
+        SSMGIPA::<IP, LMC, IPC, P, D>::prove_with_context(
+            values,
+            (
+                ck.0,
+                &vec![HomomorphicPlaceholderValue {}; values.1.len()],
+                ck.1,
+            ),
+            (srs, &(), &()),
+            &<P::Fr>::one(),
+            &Self::context(&v_srs, scalar_b)?,
+        )
     }
 
     pub fn verify_with_structured_scalar_message(
@@ -104,27 +171,24 @@ where
         ck_t: &IPC::Key,
         com: (&LMC::Output, &IPC::Output),
         scalar_b: &P::Fr,
-        proof: &TIPAWithSSMProof<IP, LMC, RMC, IPC, P, D>,
+        proof: &TIPAWithSSMProof<IP, LMC, IPC, P, D>,
     ) -> Result<bool, Error> {
-        let tipa_valid =
-            TIPA::verify(v_srs, ck_t, (com.0, &proof.com_b, com.1), &proof.tipa_proof)?;
-
-        // Check final scalar
-        //TODO: repeating gathering of transcript from TIPA verify
-        let (_, transcript) = GIPA::verify_recursive_challenge_transcript(
-            (com.0, &proof.com_b, com.1),
-            &proof.tipa_proof.gipa_proof,
-        )?;
-        let mut power_2_b = scalar_b.clone();
-        let mut product_form = Vec::new();
-        for x in transcript.iter() {
-            product_form.push(<P::Fr>::one() + &(x.inverse().unwrap() * &power_2_b));
-            power_2_b *= power_2_b;
-        }
-        let final_b = product_form.iter().product::<P::Fr>();
-        let final_b_valid = final_b == proof.tipa_proof.gipa_proof.r_base.1;
+        SSMGIPA::<IP, LMC, IPC, P, D>::verify_with_context(
+            (v_srs, &(), scalar_b),
+            ck_t,
+            (com.0, &HomomorphicPlaceholderValue {}, com.1),
+            proof,
+            &<P::Fr>::one(),
+            &Self::context(v_srs, scalar_b)?,
+        )
+    }
 
-        Ok(tipa_valid && final_b_valid)
+    // Binds the SRS and the scalar of the structured message to the transcript, since the right
+    // message is never committed to
+    fn context(v_srs: &VerifierSRS<P>, scalar_b: &P::Fr) -> Result<Vec<u8>, Error> {
+        let srs_digest = v_srs.digest::<D>()?;
+        //TODO: Should use CanonicalSerialize instead of ToBytes
+        Ok(to_bytes![srs_digest, scalar_b]?)
     }
 }
 
@@ -139,7 +203,7 @@ pub fn structured_scalar_power<F: Field>(num: usize, s: &F) -> Vec<F> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use algebra::{bls12_381::Bls12_381, curves::PairingEngine, UniformRand};
+    use algebra::{curves::PairingEngine, UniformRand};
     use blake2::Blake2b;
     use rand::{rngs::StdRng, SeedableRng};
 
@@ -149,84 +213,108 @@ mod tests {
     };
     use inner_products::{InnerProduct, MultiexponentiationInnerProduct, ScalarInnerProduct};
 
-    type GC1 = AFGHOCommitmentG1<Bls12_381>;
-    type SC1 = PedersenCommitment<<Bls12_381 as PairingEngine>::G1Projective>;
-    type SC2 = PedersenCommitment<<Bls12_381 as PairingEngine>::G2Projective>;
+    pairing_engine_tests! {
+        type GC1 = AFGHOCommitmentG1<E>;
+        type SC2 = PedersenCommitment<<E as PairingEngine>::G2Projective>;
 
-    const TEST_SIZE: usize = 8;
+        const TEST_SIZE: usize = 8;
 
-    #[test]
-    fn multiexponentiation_inner_product_test() {
-        type IP = MultiexponentiationInnerProduct<<Bls12_381 as PairingEngine>::G1Projective>;
-        type IPC = IdentityCommitment<
-            <Bls12_381 as PairingEngine>::G1Projective,
-            <Bls12_381 as PairingEngine>::Fr,
-        >;
-        type MultiExpTIPA = TIPA<IP, GC1, SC1, IPC, Bls12_381, Blake2b>;
+        #[test]
+        fn multiexponentiation_inner_product_test() {
+            type IP = MultiexponentiationInnerProduct<<E as PairingEngine>::G1Projective>;
+            type IPC = IdentityCommitment<
+                <E as PairingEngine>::G1Projective,
+                <E as PairingEngine>::Fr,
+            >;
+            type MultiExpTIPA = TIPAWithSSM<IP, GC1, IPC, E, Blake2b>;
 
-        let mut rng = StdRng::seed_from_u64(0u64);
-        let (srs, ck_t) = MultiExpTIPA::setup(&mut rng, TEST_SIZE).unwrap();
-        let (ck_a, ck_b) = srs.get_commitment_keys();
-        let v_srs = srs.get_verifier_key();
-        let m_a = random_generators(&mut rng, TEST_SIZE);
-        let b = <<Bls12_381 as PairingEngine>::Fr>::rand(&mut rng);
-        let m_b = structured_scalar_power(TEST_SIZE, &b);
-        let com_a = GC1::commit(&ck_a, &m_a).unwrap();
-        let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
-        let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
-
-        let proof = MultiExpTIPA::prove_with_structured_scalar_message(
-            &srs,
-            (&m_a, &m_b),
-            (&ck_a, &ck_b, &ck_t),
-        )
-        .unwrap();
-
-        assert!(MultiExpTIPA::verify_with_structured_scalar_message(
-            &v_srs,
-            &ck_t,
-            (&com_a, &com_t),
-            &b,
-            &proof
-        )
-        .unwrap());
-    }
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (srs, ck_t) = MultiExpTIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let (ck_a, _) = srs.get_commitment_keys();
+            let v_srs = srs.get_verifier_key();
+            let m_a = random_generators(&mut rng, TEST_SIZE);
+            let b = <<E as PairingEngine>::Fr>::rand(&mut rng);
+            let m_b = structured_scalar_power(TEST_SIZE, &b);
+            let com_a = GC1::commit(&ck_a, &m_a).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
 
-    #[test]
-    fn scalar_inner_product_test() {
-        type IP = ScalarInnerProduct<<Bls12_381 as PairingEngine>::Fr>;
-        type IPC =
-            IdentityCommitment<<Bls12_381 as PairingEngine>::Fr, <Bls12_381 as PairingEngine>::Fr>;
-        type ScalarTIPA = TIPA<IP, SC2, SC1, IPC, Bls12_381, Blake2b>;
+            let proof = MultiExpTIPA::prove_with_structured_scalar_message(
+                &srs,
+                (&m_a, &m_b),
+                (&ck_a, &ck_t),
+                &b,
+            )
+            .unwrap();
 
-        let mut rng = StdRng::seed_from_u64(0u64);
-        let (srs, ck_t) = ScalarTIPA::setup(&mut rng, TEST_SIZE).unwrap();
-        let (ck_a, ck_b) = srs.get_commitment_keys();
-        let v_srs = srs.get_verifier_key();
-        let mut m_a = Vec::new();
-        for _ in 0..TEST_SIZE {
-            m_a.push(<Bls12_381 as PairingEngine>::Fr::rand(&mut rng));
+            assert!(MultiExpTIPA::verify_with_structured_scalar_message(
+                &v_srs,
+                &ck_t,
+                (&com_a, &com_t),
+                &b,
+                &proof
+            )
+            .unwrap());
+
+            let wrong_b = <<E as PairingEngine>::Fr>::rand(&mut rng);
+            assert!(!MultiExpTIPA::verify_with_structured_scalar_message(
+                &v_srs,
+                &ck_t,
+                (&com_a, &com_t),
+                &wrong_b,
+                &proof
+            )
+            .unwrap());
+
+            // Claimed inner product not bound to the transcript
+            let wrong_com_t = IPC::commit(&vec![ck_t.clone()], &vec![t[0] + &t[0]]).unwrap();
+            assert!(!MultiExpTIPA::verify_with_structured_scalar_message(
+                &v_srs,
+                &ck_t,
+                (&com_a, &wrong_com_t),
+                &b,
+                &proof
+            )
+            .unwrap());
+        }
+
+        #[test]
+        fn scalar_inner_product_test() {
+            type IP = ScalarInnerProduct<<E as PairingEngine>::Fr>;
+            type IPC =
+                IdentityCommitment<<E as PairingEngine>::Fr, <E as PairingEngine>::Fr>;
+            type ScalarTIPA = TIPAWithSSM<IP, SC2, IPC, E, Blake2b>;
+
+            let mut rng = StdRng::seed_from_u64(0u64);
+            let (srs, ck_t) = ScalarTIPA::setup(&mut rng, TEST_SIZE).unwrap();
+            let (ck_a, _) = srs.get_commitment_keys();
+            let v_srs = srs.get_verifier_key();
+            let mut m_a = Vec::new();
+            for _ in 0..TEST_SIZE {
+                m_a.push(<E as PairingEngine>::Fr::rand(&mut rng));
+            }
+            let b = <<E as PairingEngine>::Fr>::rand(&mut rng);
+            let m_b = structured_scalar_power(TEST_SIZE, &b);
+            let com_a = SC2::commit(&ck_a, &m_a).unwrap();
+            let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
+            let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
+
+            let proof = ScalarTIPA::prove_with_structured_scalar_message(
+                &srs,
+                (&m_a, &m_b),
+                (&ck_a, &ck_t),
+                &b,
+            )
+            .unwrap();
+
+            assert!(ScalarTIPA::verify_with_structured_scalar_message(
+                &v_srs,
+                &ck_t,
+                (&com_a, &com_t),
+                &b,
+                &proof
+            )
+            .unwrap());
         }
-        let b = <<Bls12_381 as PairingEngine>::Fr>::rand(&mut rng);
-        let m_b = structured_scalar_power(TEST_SIZE, &b);
-        let com_a = SC2::commit(&ck_a, &m_a).unwrap();
-        let t = vec![IP::inner_product(&m_a, &m_b).unwrap()];
-        let com_t = IPC::commit(&vec![ck_t.clone()], &t).unwrap();
-
-        let proof = ScalarTIPA::prove_with_structured_scalar_message(
-            &srs,
-            (&m_a, &m_b),
-            (&ck_a, &ck_b, &ck_t),
-        )
-        .unwrap();
-
-        assert!(ScalarTIPA::verify_with_structured_scalar_message(
-            &v_srs,
-            &ck_t,
-            (&com_a, &com_t),
-            &b,
-            &proof
-        )
-        .unwrap());
     }
 }
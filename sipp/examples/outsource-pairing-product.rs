@@ -0,0 +1,118 @@
+#![deny(
+    unused_import_braces,
+    unused_qualifications,
+    trivial_casts,
+    trivial_numeric_casts
+)]
+#![deny(unused_qualifications, variant_size_differences, stable_features)]
+#![deny(
+    non_shorthand_field_patterns,
+    unused_attributes,
+    unused_imports,
+    unused_extern_crates
+)]
+#![deny(
+    renamed_and_removed_lints,
+    stable_features,
+    unused_allocation,
+    unused_comparisons
+)]
+#![deny(
+    unused_must_use,
+    unused_mut,
+    unused_unsafe,
+    private_in_public,
+    unsafe_code
+)]
+
+use algebra::{bls12_377::*, ProjectiveCurve, UniformRand};
+use blake2::Blake2s;
+use sipp::{
+    outsourcing::{PairingProductOutsourcing, PairingProductRequest, PairingProductResponse},
+    rng::FiatShamirRng,
+};
+use std::{
+    io::{self, BufReader, BufWriter, Write},
+    process::{Command, Stdio},
+    time::Instant,
+};
+
+type ExampleOutsourcing = PairingProductOutsourcing<Bls12_377, Blake2s>;
+
+// The server reads a request from stdin and writes the response to stdout
+fn serve() {
+    let request =
+        PairingProductRequest::<Bls12_377>::deserialize(BufReader::new(io::stdin())).unwrap();
+    let response = ExampleOutsourcing::respond(&request).unwrap();
+    let mut writer = BufWriter::new(io::stdout());
+    response.serialize(&mut writer).unwrap();
+    writer.flush().unwrap();
+}
+
+// The client spawns the server as a child process and talks to it over its stdin and stdout
+fn outsource(log_num_inputs: usize) {
+    let mut rng = FiatShamirRng::<Blake2s>::from_seed(b"falafel");
+    let m = 1 << log_num_inputs;
+    let mut a = Vec::with_capacity(m);
+    let mut b = Vec::with_capacity(m);
+    let mut r = Vec::with_capacity(m);
+    for _ in 0..m {
+        a.push(G1Projective::rand(&mut rng));
+        b.push(G2Projective::rand(&mut rng));
+        r.push(Fr::rand(&mut rng));
+    }
+    let request = PairingProductRequest::<Bls12_377> {
+        a: ProjectiveCurve::batch_normalization_into_affine(&a),
+        b: ProjectiveCurve::batch_normalization_into_affine(&b),
+        r,
+    };
+
+    let start = Instant::now();
+    let mut server = Command::new(std::env::current_exe().unwrap())
+        .arg("server")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn the server");
+    {
+        let mut writer = BufWriter::new(server.stdin.take().unwrap());
+        request.serialize(&mut writer).unwrap();
+        writer.flush().unwrap();
+    }
+    let response = PairingProductResponse::<Bls12_377>::deserialize(BufReader::new(
+        server.stdout.take().unwrap(),
+    ))
+    .unwrap();
+    assert!(server.wait().unwrap().success());
+    let server_time = start.elapsed().as_millis() as f64 / 1_000.0;
+
+    let start = Instant::now();
+    let accept = ExampleOutsourcing::verify_response(&request, &response).unwrap();
+    let verifier_time = start.elapsed().as_millis() as f64 / 1_000.0;
+
+    println!(
+        "=== Outsourcing a product of {} pairings over Bls12-377 ===",
+        m
+    );
+    println!("Round trip to the server: {:?} seconds", server_time);
+    println!("Client verification: {:?} seconds", verifier_time);
+    println!("Response accepted: {}", accept);
+    assert!(accept);
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() > 1 && (args[1] == "-h" || args[1] == "--help") {
+        println!("\nHelp: Invoke this as <program> [<log_num_inputs>] to outsource a product of pairings to a local server, or as <program> server to run the server on stdin and stdout\n");
+        println!("For example, `cargo run --release --example outsource-pairing-product 16` outsources a product of 2^16 pairings\n");
+        return;
+    }
+    if args.len() > 1 && args[1] == "server" {
+        serve();
+        return;
+    }
+    let log_num_inputs: usize = args.get(1).map_or(16, |n| {
+        n.parse().expect("<log_num_inputs> should be integer")
+    });
+    outsource(log_num_inputs);
+}
@@ -33,6 +33,9 @@ use sipp::{rng::FiatShamirRng, SIPP};
 use std::time::Instant;
 
 type ExampleSIPP = SIPP<Bls12_377, Blake2s>;
+
+// Number of proofs verified together when comparing batch and sequential verification
+const BATCH_SIZE: usize = 8;
 use serde::Serialize;
 
 #[derive(Debug, Serialize)]
@@ -41,12 +44,16 @@ struct ProfileData {
     direct: f64,
     prover: f64,
     verifier: f64,
+    sequential_verifier: f64,
+    batch_verifier: f64,
 }
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() < 2 || args[1] == "-h" || args[1] == "--help" {
+    if args.len() < 4 || args[1] == "-h" || args[1] == "--help" {
         println!("\nHelp: Invoke this as <program> <log_min_num_inputs> <log_max_num_inputs> <path_to_output_dir>\n");
+        println!("For example, `cargo run --release --example scaling-ipp 10 20 sipp/plot` reports prover and verifier times up to 2^20 inputs\n");
+        return;
     }
     let min_num_inputs: usize = String::from(args[1].clone())
         .parse()
@@ -81,30 +88,44 @@ fn main() {
         let m = 1 << i;
         let num_iters = match i {
             1..=5 => 20,
-            7..=10 => 10,
+            6..=10 => 10,
             11..=13 => 5,
             _ => 1,
         };
         let a_s = &mut a_s[..m];
         let b_s = &mut b_s[..m];
+        let r_s = &r_s[..m];
 
         let mut direct_time = 0.0;
         let mut prover_time = 0.0;
         let mut verifier_time = 0.0;
+        let mut sequential_verifier_time = 0.0;
+        let mut batch_verifier_time = 0.0;
         for _ in 0..num_iters {
             a_s.shuffle(&mut rng);
             b_s.shuffle(&mut rng);
             let start = Instant::now();
-            let z = sipp::product_of_pairings_with_coeffs::<Bls12_377>(a_s, b_s, &r_s);
+            let z = sipp::product_of_pairings_with_coeffs::<Bls12_377>(a_s, b_s, r_s);
             direct_time += (start.elapsed().as_millis() as f64) / 1_000.0;
 
             let start = Instant::now();
-            let proof = ExampleSIPP::prove(a_s, b_s, &r_s, z.clone()).unwrap();
+            let proof = ExampleSIPP::prove(a_s, b_s, r_s, z.clone()).unwrap();
             prover_time += (start.elapsed().as_millis() as f64) / 1_000.0;
 
             let start = Instant::now();
-            assert!(ExampleSIPP::verify(a_s, b_s, &r_s, z.clone(), &proof).unwrap());
+            assert!(ExampleSIPP::verify(a_s, b_s, r_s, z.clone(), &proof).unwrap());
             verifier_time += (start.elapsed().as_millis() as f64) / 1_000.0;
+
+            let instances = vec![(&a_s[..], &b_s[..], r_s, z, &proof); BATCH_SIZE];
+            let start = Instant::now();
+            for (a, b, r, z, proof) in instances.iter() {
+                assert!(ExampleSIPP::verify(a, b, r, *z, proof).unwrap());
+            }
+            sequential_verifier_time += (start.elapsed().as_millis() as f64) / 1_000.0;
+
+            let start = Instant::now();
+            assert!(ExampleSIPP::batch_verify(&instances, &mut rng).unwrap());
+            batch_verifier_time += (start.elapsed().as_millis() as f64) / 1_000.0;
         }
         let num_iters = num_iters as f64;
         println!(
@@ -114,12 +135,24 @@ fn main() {
         println!("Direct time: {:?} seconds", direct_time / num_iters);
         println!("Prover time: {:?} seconds", prover_time / num_iters);
         println!("Verifier time: {:?} seconds", verifier_time / num_iters);
+        println!(
+            "Sequential verifier time for {} proofs: {:?} seconds",
+            BATCH_SIZE,
+            sequential_verifier_time / num_iters
+        );
+        println!(
+            "Batch verifier time for {} proofs: {:?} seconds",
+            BATCH_SIZE,
+            batch_verifier_time / num_iters
+        );
         println!();
         let d = ProfileData {
             size: m,
             direct: direct_time / num_iters,
             prover: prover_time / num_iters,
             verifier: verifier_time / num_iters,
+            sequential_verifier: sequential_verifier_time / num_iters,
+            batch_verifier: batch_verifier_time / num_iters,
         };
         wtr.serialize(d).unwrap();
     }
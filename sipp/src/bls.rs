@@ -0,0 +1,234 @@
+use algebra_core::{AffineCurve, One, PairingEngine, ProjectiveCurve, UniformRand};
+use dh_commitments::hash_to_generators;
+use digest::Digest;
+use rand_core::RngCore;
+use std::{collections::HashSet, marker::PhantomData};
+
+use crate::{product_of_pairings, Proof, SIPPError, SIPP};
+
+const HASH_TO_G1_DOMAIN: &[u8] = b"ripp-bls-hash-to-g1";
+
+/// BLS signatures with signatures and hashed messages in G1 and public keys in G2,
+/// so that aggregate verification is an inner pairing product provable with SIPP.
+pub struct BLS<E: PairingEngine, D: Digest> {
+    _engine: PhantomData<E>,
+    _digest: PhantomData<D>,
+}
+
+impl<E: PairingEngine, D: Digest> BLS<E, D> {
+    /// Hash a message to G1 as a generator derived by `dh_commitments` from the message under
+    /// the BLS label, so that it is separated from commitment keys. Not constant time in the message.
+    pub fn hash_to_g1(message: &[u8]) -> E::G1Affine {
+        let mut label = HASH_TO_G1_DOMAIN.to_vec();
+        label.extend_from_slice(message);
+        // Only fails to serialize the subgroup generator into memory
+        hash_to_generators::<D, E::G1Projective>(&label, 1)
+            .unwrap()
+            .pop()
+            .unwrap()
+            .into_affine()
+    }
+
+    /// Sample a secret key and its public key.
+    pub fn keygen<R: RngCore>(rng: &mut R) -> (E::Fr, E::G2Affine) {
+        let sk = E::Fr::rand(rng);
+        let pk = E::G2Affine::prime_subgroup_generator().mul(sk).into_affine();
+        (sk, pk)
+    }
+
+    /// Sign a message.
+    pub fn sign(sk: &E::Fr, message: &[u8]) -> E::G1Affine {
+        Self::hash_to_g1(message).mul(*sk).into_affine()
+    }
+
+    /// Verify a single signature with two pairings.
+    pub fn verify(pk: &E::G2Affine, message: &[u8], signature: &E::G1Affine) -> bool {
+        E::pairing(*signature, E::G2Affine::prime_subgroup_generator())
+            == E::pairing(Self::hash_to_g1(message), *pk)
+    }
+
+    /// Aggregate signatures on distinct messages into a single signature.
+    pub fn aggregate(signatures: &[E::G1Affine]) -> E::G1Affine {
+        let mut aggregate_signature = E::G1Projective::default();
+        for signature in signatures {
+            aggregate_signature.add_assign_mixed(signature);
+        }
+        aggregate_signature.into_affine()
+    }
+
+    /// Verify an aggregate signature directly with one pairing per message.
+    pub fn verify_aggregate(
+        pks: &[E::G2Affine],
+        messages: &[&[u8]],
+        aggregate_signature: &E::G1Affine,
+    ) -> bool {
+        if pks.is_empty() || pks.len() != messages.len() || !Self::distinct(messages) {
+            return false;
+        }
+        let hashes = messages
+            .iter()
+            .map(|message| Self::hash_to_g1(message))
+            .collect::<Vec<_>>();
+        E::pairing(*aggregate_signature, E::G2Affine::prime_subgroup_generator())
+            == product_of_pairings::<E>(&hashes, pks)
+    }
+
+    /// Aggregate signatures on distinct messages and prove, for the untrusted aggregator,
+    /// that the product of pairings between hashed messages and public keys equals the pairing
+    /// of the aggregate signature and the generator with SIPP.
+    pub fn aggregate_with_proof(
+        pks: &[E::G2Affine],
+        messages: &[&[u8]],
+        signatures: &[E::G1Affine],
+    ) -> Result<(E::G1Affine, Proof<E>), SIPPError> {
+        if pks.len() != messages.len() {
+            return Err(SIPPError::LengthMismatch(pks.len(), messages.len()));
+        }
+        if pks.len() != signatures.len() {
+            return Err(SIPPError::LengthMismatch(pks.len(), signatures.len()));
+        }
+        // Rejected by the verifier, as are duplicate messages
+        if pks.is_empty() {
+            return Err(SIPPError::EmptyInput);
+        }
+        if !Self::distinct(messages) {
+            return Err(SIPPError::DuplicateMessages);
+        }
+        let aggregate_signature = Self::aggregate(signatures);
+        let (a, b, r) = Self::pairing_product_instance(pks, messages, &aggregate_signature);
+        let proof = SIPP::<E, D>::prove(&a, &b, &r, E::Fqk::one())?;
+        Ok((aggregate_signature, proof))
+    }
+
+    /// Verify an aggregate signature using the aggregator's SIPP proof, with a single pairing
+    /// and two multi-scalar multiplications instead of one pairing per message.
+    pub fn verify_aggregate_with_proof(
+        pks: &[E::G2Affine],
+        messages: &[&[u8]],
+        aggregate_signature: &E::G1Affine,
+        proof: &Proof<E>,
+    ) -> Result<bool, SIPPError> {
+        if pks.is_empty() || pks.len() != messages.len() || !Self::distinct(messages) {
+            return Ok(false);
+        }
+        let (a, b, r) = Self::pairing_product_instance(pks, messages, aggregate_signature);
+        SIPP::<E, D>::verify(&a, &b, &r, E::Fqk::one(), proof)
+    }
+
+    // Hashed messages and public keys followed by the pair (signature, -h), so that the product of
+    // pairings is one for a valid aggregate signature and the verifier computes no pairing besides
+    // the final one of SIPP. Padded to a power of two with pairs (0, h) that do not affect the
+    // product of pairings.
+    fn pairing_product_instance(
+        pks: &[E::G2Affine],
+        messages: &[&[u8]],
+        aggregate_signature: &E::G1Affine,
+    ) -> (Vec<E::G1Affine>, Vec<E::G2Affine>, Vec<E::Fr>) {
+        let length = (pks.len() + 1).next_power_of_two();
+        let mut a = messages
+            .iter()
+            .map(|message| Self::hash_to_g1(message))
+            .collect::<Vec<_>>();
+        a.push(*aggregate_signature);
+        a.resize(length, E::G1Affine::default());
+        let mut b = pks.to_vec();
+        b.push(-E::G2Affine::prime_subgroup_generator());
+        b.resize(length, E::G2Affine::prime_subgroup_generator());
+        let r = vec![E::Fr::one(); length];
+        (a, b, r)
+    }
+
+    fn distinct(messages: &[&[u8]]) -> bool {
+        let mut seen = HashSet::new();
+        messages.iter().all(|message| seen.insert(*message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::FiatShamirRng;
+    use algebra::bls12_377::{Bls12_377, Fr};
+    use algebra_core::{to_bytes, ToBytes};
+    use blake2::{Blake2b, Blake2s};
+
+    type TestBLS = BLS<Bls12_377, Blake2b>;
+
+    #[test]
+    fn sign_and_verify() {
+        let mut rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![b"falafel"].unwrap());
+        let (sk, pk) = TestBLS::keygen(&mut rng);
+        let signature = TestBLS::sign(&sk, b"message");
+        assert!(TestBLS::verify(&pk, b"message", &signature));
+        assert!(!TestBLS::verify(&pk, b"other message", &signature));
+    }
+
+    #[test]
+    fn aggregate_with_proof_matches_naive_verification() {
+        let mut rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![b"falafel"].unwrap());
+        let messages_owned = (0..5u8)
+            .map(|i| vec![b'm', i])
+            .collect::<Vec<Vec<u8>>>();
+        let messages = messages_owned.iter().map(|m| m.as_slice()).collect::<Vec<_>>();
+        let mut pks = Vec::new();
+        let mut signatures = Vec::new();
+        for message in messages.iter() {
+            let (sk, pk) = TestBLS::keygen(&mut rng);
+            pks.push(pk);
+            signatures.push(TestBLS::sign(&sk, message));
+        }
+
+        let (aggregate_signature, proof) =
+            TestBLS::aggregate_with_proof(&pks, &messages, &signatures).unwrap();
+        assert!(TestBLS::verify_aggregate(&pks, &messages, &aggregate_signature));
+        assert!(
+            TestBLS::verify_aggregate_with_proof(&pks, &messages, &aggregate_signature, &proof)
+                .unwrap()
+        );
+
+        // Invalid signature among the aggregated ones
+        let mut wrong_signatures = signatures.clone();
+        wrong_signatures[2] = TestBLS::sign(&Fr::one(), messages[2]);
+        let (wrong_aggregate_signature, wrong_proof) =
+            TestBLS::aggregate_with_proof(&pks, &messages, &wrong_signatures).unwrap();
+        assert!(!TestBLS::verify_aggregate(
+            &pks,
+            &messages,
+            &wrong_aggregate_signature
+        ));
+        assert!(!TestBLS::verify_aggregate_with_proof(
+            &pks,
+            &messages,
+            &wrong_aggregate_signature,
+            &wrong_proof
+        )
+        .unwrap());
+
+        // Swapped messages
+        let mut swapped_messages = messages.clone();
+        swapped_messages.swap(0, 1);
+        assert!(!TestBLS::verify_aggregate(
+            &pks,
+            &swapped_messages,
+            &aggregate_signature
+        ));
+        assert!(!TestBLS::verify_aggregate_with_proof(
+            &pks,
+            &swapped_messages,
+            &aggregate_signature,
+            &proof
+        )
+        .unwrap());
+
+        // Inputs the verifier rejects are not aggregated
+        match TestBLS::aggregate_with_proof(&[], &[], &[]) {
+            Err(SIPPError::EmptyInput) => (),
+            _ => panic!("expected empty input"),
+        }
+        let duplicate_messages = vec![messages[0], messages[0]];
+        match TestBLS::aggregate_with_proof(&pks[..2], &duplicate_messages, &signatures[..2]) {
+            Err(SIPPError::DuplicateMessages) => (),
+            _ => panic!("expected duplicate messages"),
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use algebra_core::{
+    fields::{
+        fp12_2over3over2::{Fp12, Fp12Parameters},
+        fp6_3over2::Fp6,
+    },
+    Field, FromBytes, One, ToBytes, Zero,
+};
+use std::io::{Error, ErrorKind, Read, Result as IoResult, Write};
+
+/// Compressed encoding of elements of the cyclotomic subgroup of a target group field,
+/// such as the GT elements of a SIPP proof.
+pub trait CyclotomicCompression: Sized {
+    /// Write an element of the cyclotomic subgroup in compressed form.
+    fn write_compressed<W: Write>(&self, writer: W) -> IoResult<()>;
+
+    /// Read an element in compressed form; the result always lies in the cyclotomic subgroup.
+    fn read_compressed<R: Read>(reader: R) -> IoResult<Self>;
+}
+
+// Torus-based compression: an element f = c0 + c1 * w of the cyclotomic subgroup has norm
+// c0^2 - w^2 * c1^2 = 1, and for c1 != 0 is recovered from g = (1 + c0) / c1 as
+// f = (g + w) / (g - w). The remaining elements are +1 and -1.
+impl<P: Fp12Parameters> CyclotomicCompression for Fp12<P> {
+    fn write_compressed<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        if self.c1.is_zero() {
+            let flag: u8 = if self.c0.is_one() { 1 } else { 2 };
+            flag.write(&mut writer)
+        } else {
+            0u8.write(&mut writer)?;
+            ((Fp6::<P::Fp6Params>::one() + &self.c0) / &self.c1).write(&mut writer)
+        }
+    }
+
+    fn read_compressed<R: Read>(mut reader: R) -> IoResult<Self> {
+        match u8::read(&mut reader)? {
+            0 => {
+                let g = Fp6::<P::Fp6Params>::read(&mut reader)?;
+                // w^2 lies in Fp6
+                let w_square = Self::new(Fp6::zero(), Fp6::one()).square().c0;
+                let g_square = g.square();
+                let denominator_inverse = (g_square - &w_square).inverse().ok_or_else(|| {
+                    Error::new(ErrorKind::InvalidData, "invalid compressed element")
+                })?;
+                Ok(Self::new(
+                    (g_square + &w_square) * &denominator_inverse,
+                    g.double() * &denominator_inverse,
+                ))
+            }
+            1 => Ok(Self::one()),
+            2 => Ok(-Self::one()),
+            _ => Err(Error::new(ErrorKind::InvalidData, "invalid compression flag")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use algebra::bls12_377::{Bls12_377, Fq12, G1Projective, G2Projective};
+    use algebra_core::{PairingEngine, UniformRand};
+    use blake2::Blake2s;
+
+    use crate::rng::FiatShamirRng;
+
+    #[test]
+    fn compression_roundtrip() {
+        let mut rng = FiatShamirRng::<Blake2s>::from_seed(b"falafel");
+        let mut elements = vec![Fq12::one(), -Fq12::one()];
+        for _ in 0..4 {
+            elements.push(Bls12_377::pairing(
+                G1Projective::rand(&mut rng),
+                G2Projective::rand(&mut rng),
+            ));
+        }
+        for element in elements {
+            let mut bytes = Vec::new();
+            element.write_compressed(&mut bytes).unwrap();
+            assert_eq!(Fq12::read_compressed(&bytes[..]).unwrap(), element);
+        }
+    }
+}
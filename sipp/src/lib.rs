@@ -8,17 +8,82 @@
 #![forbid(unsafe_code)]
 
 use algebra_core::{
-    msm::VariableBaseMSM, to_bytes, AffineCurve, Field, One, PairingEngine, PrimeField,
+    msm::VariableBaseMSM, to_bytes, AffineCurve, Field, FromBytes, One, PairingEngine, PrimeField,
     ProjectiveCurve, ToBytes, UniformRand,
 };
 use digest::Digest;
+use rand_core::RngCore;
 use rayon::prelude::*;
-use std::marker::PhantomData;
+use std::{
+    error::Error as ErrorTrait,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::{self, Read, Write},
+    marker::PhantomData,
+    slice::Iter,
+};
+
+use dh_commitments::identity::{IdentityCommitment, IdentityOutput};
+use inner_products::{ExtensionFieldElement, PairingInnerProduct};
+use ip_proofs::{
+    fiat_shamir::hash_to_challenge,
+    gipa::{GIPAProof, GIPA},
+    tipa::structured_scalar_message::structured_scalar_power,
+};
 
 /// Fiat-Shamir Rng
 pub mod rng;
 
-use rng::FiatShamirRng;
+/// BLS aggregate signatures verified with SIPP
+pub mod bls;
+
+/// Compressed encoding of target group elements
+pub mod compression;
+
+/// Verifiable outsourcing of pairing products
+pub mod outsourcing;
+
+use compression::CyclotomicCompression;
+
+const SIPP_PROTOCOL: &[u8] = b"SIPP";
+const SIPP_STRUCTURED_COEFFICIENTS_PROTOCOL: &[u8] = b"SIPP-structured-coefficients";
+// Changed whenever the transcript of SIPP changes
+const SIPP_VERSION: u32 = 2;
+
+/// A label for domain separation of Fiat-Shamir transcripts, so that challenges are bound to
+/// the protocol, its version, the bilinear group and the size of the statement.
+#[derive(Clone, Copy, Debug)]
+pub struct DomainSeparator<'a> {
+    /// The name of the protocol.
+    pub protocol: &'a [u8],
+    /// The version of the protocol, changed whenever its transcript changes.
+    pub version: u32,
+    /// An identifier of the bilinear group, such as an encoding of its generators.
+    pub curve: &'a [u8],
+    /// The length of the vectors in the statement.
+    pub length: u64,
+}
+
+// Variable length fields are prefixed with their length so that the encoding is injective
+impl<'a> ToBytes for DomainSeparator<'a> {
+    fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        (self.protocol.len() as u64).write(&mut writer)?;
+        writer.write_all(self.protocol)?;
+        self.version.write(&mut writer)?;
+        (self.curve.len() as u64).write(&mut writer)?;
+        writer.write_all(self.curve)?;
+        self.length.write(&mut writer)
+    }
+}
+
+// SIPP is GIPA over the pairing inner product with both vectors public, i.e. with identity
+// commitments, so that the verifier folds the vectors itself.
+type PairingGIPA<E, D> = GIPA<
+    PairingInnerProduct<E>,
+    IdentityCommitment<<E as PairingEngine>::G1Projective, <E as PairingEngine>::Fr>,
+    IdentityCommitment<<E as PairingEngine>::G2Projective, <E as PairingEngine>::Fr>,
+    IdentityCommitment<ExtensionFieldElement<E>, <E as PairingEngine>::Fr>,
+    D,
+>;
 
 /// SIPP is a inner-pairing product proof that allows a verifier to check an
 /// inner-pairing product over `n` elements with only a single pairing.
@@ -33,6 +98,122 @@ pub struct Proof<E: PairingEngine> {
     gt_elems: Vec<(E::Fqk, E::Fqk)>,
 }
 
+impl<E: PairingEngine> Proof<E> {
+    /// Construct a proof from the GT elements of each round.
+    pub fn new(gt_elems: Vec<(E::Fqk, E::Fqk)>) -> Self {
+        Self { gt_elems }
+    }
+
+    /// The number of rounds, i.e. the log of the length of the input vectors.
+    pub fn len(&self) -> usize {
+        self.gt_elems.len()
+    }
+
+    /// Whether the proof has no rounds, as for input vectors of length 1.
+    pub fn is_empty(&self) -> bool {
+        self.gt_elems.is_empty()
+    }
+
+    /// Iterate over the left and right GT elements of each round.
+    pub fn iter(&self) -> Iter<'_, (E::Fqk, E::Fqk)> {
+        self.gt_elems.iter()
+    }
+}
+
+impl<E: PairingEngine> Proof<E>
+where
+    E::Fqk: CyclotomicCompression,
+{
+    /// Serialize the proof with GT elements in cyclotomic compressed form.
+    pub fn serialize<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        (self.gt_elems.len() as u64).write(&mut writer)?;
+        for (z_l, z_r) in self.gt_elems.iter() {
+            z_l.write_compressed(&mut writer)?;
+            z_r.write_compressed(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// Deserialize a proof, checking that every GT element lies in the prime order subgroup.
+    pub fn deserialize<R: Read>(mut reader: R) -> Result<Self, SIPPError> {
+        let len = u64::read(&mut reader)?;
+        // Proofs have one round per bit of the input length
+        if len > 64 {
+            return Err(SIPPError::ProofLengthInvalid(len));
+        }
+        let mut gt_elems = Vec::new();
+        for _ in 0..len {
+            let z_l = E::Fqk::read_compressed(&mut reader)?;
+            let z_r = E::Fqk::read_compressed(&mut reader)?;
+            if !Self::in_subgroup(&z_l) || !Self::in_subgroup(&z_r) {
+                return Err(SIPPError::NotInSubgroup);
+            }
+            gt_elems.push((z_l, z_r));
+        }
+        Ok(Self { gt_elems })
+    }
+
+    // Decompressed elements lie in the cyclotomic subgroup, which contains GT
+    fn in_subgroup(elem: &E::Fqk) -> bool {
+        elem.pow(E::Fr::characteristic()).is_one()
+    }
+}
+
+/// Errors returned by SIPP on malformed inputs.
+#[derive(Debug)]
+pub enum SIPPError {
+    /// The input vectors have different lengths.
+    LengthMismatch(usize, usize),
+    /// The length of the input vectors is not a power of two.
+    LengthNotPowerOfTwo(usize),
+    /// The statement could not be serialized for Fiat-Shamir.
+    Serialization(io::Error),
+    /// The underlying inner product argument failed.
+    InnerProductArgument(String),
+    /// A serialized proof has more rounds than any input length allows.
+    ProofLengthInvalid(u64),
+    /// A deserialized GT element is not in the prime order subgroup.
+    NotInSubgroup,
+    /// The input vectors are empty.
+    EmptyInput,
+    /// The signed messages of an aggregate signature are not distinct.
+    DuplicateMessages,
+}
+
+impl ErrorTrait for SIPPError {
+    fn source(&self) -> Option<&(dyn ErrorTrait + 'static)> {
+        match self {
+            SIPPError::Serialization(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for SIPPError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            SIPPError::LengthMismatch(left, right) => {
+                write!(f, "left length, right length: {}, {}", left, right)
+            }
+            SIPPError::LengthNotPowerOfTwo(length) => {
+                write!(f, "length is not a power of two: {}", length)
+            }
+            SIPPError::Serialization(e) => write!(f, "serialization failed: {}", e),
+            SIPPError::InnerProductArgument(e) => write!(f, "inner product argument: {}", e),
+            SIPPError::ProofLengthInvalid(len) => write!(f, "invalid proof length: {}", len),
+            SIPPError::NotInSubgroup => write!(f, "element not in subgroup"),
+            SIPPError::EmptyInput => write!(f, "empty input"),
+            SIPPError::DuplicateMessages => write!(f, "messages are not distinct"),
+        }
+    }
+}
+
+impl From<io::Error> for SIPPError {
+    fn from(e: io::Error) -> Self {
+        SIPPError::Serialization(e)
+    }
+}
+
 impl<E: PairingEngine, D: Digest> SIPP<E, D> {
     /// Produce a proof of the inner pairing product.
     pub fn prove(
@@ -40,16 +221,34 @@ impl<E: PairingEngine, D: Digest> SIPP<E, D> {
         b: &[E::G2Affine],
         r: &[E::Fr],
         value: E::Fqk,
-    ) -> Result<Proof<E>, ()> {
-        assert_eq!(a.len(), b.len());
-        // Ensure the order of the input vectors is a power of 2
-        assert_eq!(a.len().count_ones(), 1);
-        let mut length = a.len();
-        assert_eq!(length, b.len());
-        assert_eq!(length.count_ones(), 1);
-        let mut proof_vec = Vec::new();
-        // TODO(psi): should we also input a succinct bilinear group description to the rng?
-        let mut rng = FiatShamirRng::<D>::from_seed(&to_bytes![a, b, r, value].unwrap());
+    ) -> Result<Proof<E>, SIPPError> {
+        check_lengths::<E>(a, b, r.len())?;
+        let transcript = Self::initial_transcript(a, b, r, &value)?;
+        Self::prove_from_transcript(a, b, r, &transcript)
+    }
+
+    /// Produce a proof of the inner pairing product with coefficients `r = (1, s, s^2, ...)`.
+    pub fn prove_with_structured_coefficients(
+        a: &[E::G1Affine],
+        b: &[E::G2Affine],
+        s: &E::Fr,
+        value: E::Fqk,
+    ) -> Result<Proof<E>, SIPPError> {
+        check_lengths::<E>(a, b, a.len())?;
+        let transcript = Self::structured_initial_transcript(a, b, s, &value)?;
+        let r = structured_scalar_power(a.len(), s);
+        Self::prove_from_transcript(a, b, &r, &transcript)
+    }
+
+    // Runs the GIPA prover for the pairing inner product, folding the affine vectors in parallel with
+    // batch normalization rather than through the generic projective folding of GIPA. Challenges are
+    // derived exactly as by GIPA, so that proofs verify against its transcript.
+    fn prove_from_transcript(
+        a: &[E::G1Affine],
+        b: &[E::G2Affine],
+        r: &[E::Fr],
+        transcript: &E::Fr,
+    ) -> Result<Proof<E>, SIPPError> {
         let a = a
             .into_par_iter()
             .zip(r)
@@ -57,20 +256,31 @@ impl<E: PairingEngine, D: Digest> SIPP<E, D> {
             .collect::<Vec<_>>();
         let mut a = E::G1Projective::batch_normalization_into_affine(&a);
         let mut b = b.to_vec();
+        let mut transcript = *transcript;
+        let mut proof_vec = Vec::new();
 
-        while length != 1 {
-            length /= 2;
-            let a_l = &a[..length];
-            let a_r = &a[length..];
-
-            let b_l = &b[..length];
-            let b_r = &b[length..];
+        while a.len() != 1 {
+            let length = a.len() / 2;
+            let (a_l, a_r) = a.split_at(length);
+            let (b_l, b_r) = b.split_at(length);
 
             let z_l = product_of_pairings::<E>(a_r, b_l);
             let z_r = product_of_pairings::<E>(a_l, b_r);
+            let (x, x_inv) = PairingGIPA::<E, D>::round_challenge(
+                &transcript,
+                (
+                    &IdentityOutput(Vec::new()),
+                    &IdentityOutput(Vec::new()),
+                    &IdentityOutput(vec![ExtensionFieldElement(z_l)]),
+                ),
+                (
+                    &IdentityOutput(Vec::new()),
+                    &IdentityOutput(Vec::new()),
+                    &IdentityOutput(vec![ExtensionFieldElement(z_r)]),
+                ),
+            )
+            .map_err(|e| SIPPError::InnerProductArgument(e.to_string()))?;
             proof_vec.push((z_l, z_r));
-            rng.absorb(&to_bytes![z_l, z_r].unwrap());
-            let x: E::Fr = u128::rand(&mut rng).into();
 
             let a_proj = a_l
                 .par_iter()
@@ -81,9 +291,6 @@ impl<E: PairingEngine, D: Digest> SIPP<E, D> {
                     temp
                 })
                 .collect::<Vec<_>>();
-            a = E::G1Projective::batch_normalization_into_affine(&a_proj);
-
-            let x_inv = x.inverse().unwrap();
             let b_proj = b_l
                 .par_iter()
                 .zip(b_r)
@@ -93,12 +300,12 @@ impl<E: PairingEngine, D: Digest> SIPP<E, D> {
                     temp
                 })
                 .collect::<Vec<_>>();
+            a = E::G1Projective::batch_normalization_into_affine(&a_proj);
             b = E::G2Projective::batch_normalization_into_affine(&b_proj);
+            transcript = x;
         }
 
-        Ok(Proof {
-            gt_elems: proof_vec,
-        })
+        Ok(Proof::new(proof_vec))
     }
 
     /// Verify an inner-pairing-product proof.
@@ -108,72 +315,254 @@ impl<E: PairingEngine, D: Digest> SIPP<E, D> {
         r: &[E::Fr],
         claimed_value: E::Fqk,
         proof: &Proof<E>,
-    ) -> Result<bool, ()> {
-        // Ensure the order of the input vectors is a power of 2
-        let length = a.len();
-        assert_eq!(length.count_ones(), 1);
-        assert!(length >= 2);
-        assert_eq!(length, b.len());
-        // Ensure there are the correct number of proof elements
-        let proof_len = proof.gt_elems.len();
-        assert_eq!(proof_len as f32, f32::log2(length as f32));
+    ) -> Result<bool, SIPPError> {
+        match Self::final_check_instance(a, b, r, claimed_value, proof)? {
+            Some((a_prime, b_prime, z_prime)) => Ok(E::pairing(a_prime, b_prime) == z_prime),
+            None => Ok(false),
+        }
+    }
 
-        // TODO(psi): should we also input a succinct bilinear group description to the rng?
-        let mut rng = FiatShamirRng::<D>::from_seed(&to_bytes![a, b, r, claimed_value].unwrap());
+    /// Verify several inner-pairing-product proofs at once, given as tuples of the statement
+    /// `(a, b, r, claimed_value)` and its proof. The final checks `e(a', b') == z'` of all instances
+    /// are combined with random coefficients from `rng` into a single multi-pairing. An empty batch
+    /// is rejected.
+    pub fn batch_verify<R: RngCore>(
+        instances: &[(&[E::G1Affine], &[E::G2Affine], &[E::Fr], E::Fqk, &Proof<E>)],
+        rng: &mut R,
+    ) -> Result<bool, SIPPError> {
+        if instances.is_empty() {
+            return Err(SIPPError::EmptyInput);
+        }
+        let mut pairs = Vec::with_capacity(instances.len());
+        let mut combined_value = E::Fqk::one();
+        for (a, b, r, claimed_value, proof) in instances {
+            let (a_prime, b_prime, z_prime) =
+                match Self::final_check_instance(a, b, r, *claimed_value, proof)? {
+                    Some(final_check) => final_check,
+                    None => return Ok(false),
+                };
+            // Check prod_i e(rho_i * a'_i, b'_i) == prod_i z'_i^rho_i
+            let rho = E::Fr::rand(rng);
+            combined_value *= &z_prime.pow(rho.into_repr());
+            pairs.push((
+                E::G1Prepared::from(a_prime.mul(rho).into_affine()),
+                E::G2Prepared::from(b_prime.into_affine()),
+            ));
+        }
+        let accept = E::final_exponentiation(&E::miller_loop(pairs.iter()))
+            .map_or(false, |combined_pairing| combined_pairing == combined_value);
+        Ok(accept)
+    }
 
-        let x_s = proof
-            .gt_elems
+    /// Verify an inner-pairing-product proof with coefficients `r = (1, s, s^2, ...)`. Only `s` is
+    /// hashed, and the combined scalars of the final check are computed in product form.
+    pub fn verify_with_structured_coefficients(
+        a: &[E::G1Affine],
+        b: &[E::G2Affine],
+        s: &E::Fr,
+        claimed_value: E::Fqk,
+        proof: &Proof<E>,
+    ) -> Result<bool, SIPPError> {
+        check_lengths::<E>(a, b, a.len())?;
+        let transcript = Self::structured_initial_transcript(a, b, s, &claimed_value)?;
+        let (x_s, z_prime) =
+            match Self::verify_challenges(a.len(), claimed_value, proof, &transcript)? {
+                Some(challenges) => challenges,
+                None => return Ok(false),
+            };
+
+        // s^i is the product of s^(2^j) over the bits j set in i, so the coefficients fold into the
+        // challenges
+        let mut s_square = *s;
+        let x_s_with_coefficients = x_s
             .iter()
-            .map(|(z_l, z_r)| {
-                rng.absorb(&to_bytes![z_l, z_r].unwrap());
-                let x: E::Fr = u128::rand(&mut rng).into();
-                x
+            .map(|x| {
+                let x_with_coefficient = *x * &s_square;
+                s_square.square_in_place();
+                x_with_coefficient
             })
             .collect::<Vec<_>>();
+        let s = Self::challenge_exponents(&x_s_with_coefficients, a.len());
+        let (a_prime, b_prime) = Self::fold_vectors(a, b, s, x_s);
+        Ok(E::pairing(a_prime, b_prime) == z_prime)
+    }
 
-        let mut x_invs = x_s.clone();
-        algebra_core::batch_inversion(&mut x_invs);
+    // Recovers the folded vectors and claimed value `(a', b', z')` of the final check, or `None` if
+    // the proof has the wrong number of elements
+    fn final_check_instance(
+        a: &[E::G1Affine],
+        b: &[E::G2Affine],
+        r: &[E::Fr],
+        claimed_value: E::Fqk,
+        proof: &Proof<E>,
+    ) -> Result<Option<(E::G1Projective, E::G2Projective, E::Fqk)>, SIPPError> {
+        check_lengths::<E>(a, b, r.len())?;
+        let transcript = Self::initial_transcript(a, b, r, &claimed_value)?;
+        let (x_s, z_prime) =
+            match Self::verify_challenges(a.len(), claimed_value, proof, &transcript)? {
+                Some(challenges) => challenges,
+                None => return Ok(None),
+            };
 
-        let z_prime = claimed_value
-            * &proof
+        // GIPA returns the challenges in reverse order, so that the i-th challenge multiplies the
+        // exponents of the indices with bit i set
+        let s = Self::challenge_exponents(&x_s, a.len())
+            .into_par_iter()
+            .zip(r)
+            .map(|(x, r)| x * r)
+            .collect::<Vec<_>>();
+        let (a_prime, b_prime) = Self::fold_vectors(a, b, s, x_s);
+        Ok(Some((a_prime, b_prime, z_prime)))
+    }
+
+    // Replays the GIPA transcript to recover the challenges and the folded claimed value, or `None`
+    // if the proof has the wrong number of elements
+    fn verify_challenges(
+        length: usize,
+        claimed_value: E::Fqk,
+        proof: &Proof<E>,
+        transcript: &E::Fr,
+    ) -> Result<Option<(Vec<E::Fr>, E::Fqk)>, SIPPError> {
+        // A proof with the wrong number of elements is rejected
+        let proof_len = proof.gt_elems.len();
+        if proof_len != length.trailing_zeros() as usize {
+            return Ok(None);
+        }
+
+        // The base messages are recomputed by the verifier below, and are not needed to recover the
+        // challenges
+        let gipa_proof = GIPAProof::new(
+            proof
                 .gt_elems
-                .par_iter()
-                .zip(&x_s)
-                .zip(&x_invs)
-                .map(|(((z_l, z_r), x), x_inv)| {
-                    z_l.pow(x.into_repr()) * &z_r.pow(x_inv.into_repr())
+                .iter()
+                .rev()
+                .map(|(z_l, z_r)| {
+                    (
+                        (
+                            IdentityOutput(Vec::new()),
+                            IdentityOutput(Vec::new()),
+                            IdentityOutput(vec![ExtensionFieldElement(*z_l)]),
+                        ),
+                        (
+                            IdentityOutput(Vec::new()),
+                            IdentityOutput(Vec::new()),
+                            IdentityOutput(vec![ExtensionFieldElement(*z_r)]),
+                        ),
+                    )
                 })
-                .reduce(|| E::Fqk::one(), |a, b| a * &b);
-
-        let mut s: Vec<E::Fr> = vec![E::Fr::one(); length];
-        let mut s_invs: Vec<E::Fr> = vec![E::Fr::one(); length];
-        // TODO(psi): batch verify
-        for (j, (x, x_inv)) in x_s.into_iter().zip(x_invs).enumerate() {
-            for i in 0..length {
-                if i & (1 << (proof_len - j - 1)) != 0 {
-                    s[i] *= &x;
-                    s_invs[i] *= &x_inv;
-                }
-            }
-        }
+                .collect(),
+            (Default::default(), Default::default()),
+        );
+        let ((_, _, com_z), x_s) =
+            PairingGIPA::<E, D>::verify_recursive_challenge_transcript_from(
+                (
+                    &IdentityOutput(Vec::new()),
+                    &IdentityOutput(Vec::new()),
+                    &IdentityOutput(vec![ExtensionFieldElement(claimed_value)]),
+                ),
+                &gipa_proof,
+                transcript,
+            )
+            .map_err(|e| SIPPError::InnerProductArgument(e.to_string()))?;
+        let z_prime = (com_z.0)[0].0;
 
-        let s = s
-            .into_iter()
-            .zip(r)
-            .map(|(x, r)| (x * r).into_repr())
-            .collect::<Vec<_>>();
+        Ok(Some((x_s, z_prime)))
+    }
+
+    // Folds the vectors with the combined scalars `s` for `a` and the challenge inverses for `b`
+    fn fold_vectors(
+        a: &[E::G1Affine],
+        b: &[E::G2Affine],
+        s: Vec<E::Fr>,
+        mut x_invs: Vec<E::Fr>,
+    ) -> (E::G1Projective, E::G2Projective) {
+        algebra_core::batch_inversion(&mut x_invs);
+        let s_invs = Self::challenge_exponents(&x_invs, a.len());
+
+        let s = s.into_par_iter().map(|x| x.into_repr()).collect::<Vec<_>>();
         let s_invs = s_invs
-            .iter()
+            .par_iter()
             .map(|x_inv| x_inv.into_repr())
             .collect::<Vec<_>>();
 
-        let a_prime = VariableBaseMSM::multi_scalar_mul(&a, &s);
-        let b_prime = VariableBaseMSM::multi_scalar_mul(&b, &s_invs);
+        let a_prime = VariableBaseMSM::multi_scalar_mul(a, &s);
+        let b_prime = VariableBaseMSM::multi_scalar_mul(b, &s_invs);
+        (a_prime, b_prime)
+    }
 
-        let accept = E::pairing(a_prime, b_prime) == z_prime;
+    // Seeds the GIPA transcript with the statement, since the public vectors are never committed to
+    fn initial_transcript(
+        a: &[E::G1Affine],
+        b: &[E::G2Affine],
+        r: &[E::Fr],
+        value: &E::Fqk,
+    ) -> Result<E::Fr, SIPPError> {
+        let curve = curve_identifier::<E>()?;
+        let domain = DomainSeparator {
+            protocol: SIPP_PROTOCOL,
+            version: SIPP_VERSION,
+            curve: &curve,
+            length: a.len() as u64,
+        };
+        Ok(hash_to_challenge::<E::Fr, D>(&to_bytes![domain, a, b, r, value]?))
+    }
 
-        Ok(accept)
+    // Seeds the GIPA transcript with the statement for coefficients `r = (1, s, s^2, ...)`, which
+    // is domain separated from arbitrary coefficients
+    fn structured_initial_transcript(
+        a: &[E::G1Affine],
+        b: &[E::G2Affine],
+        s: &E::Fr,
+        value: &E::Fqk,
+    ) -> Result<E::Fr, SIPPError> {
+        let curve = curve_identifier::<E>()?;
+        let domain = DomainSeparator {
+            protocol: SIPP_STRUCTURED_COEFFICIENTS_PROTOCOL,
+            version: SIPP_VERSION,
+            curve: &curve,
+            length: a.len() as u64,
+        };
+        Ok(hash_to_challenge::<E::Fr, D>(&to_bytes![domain, a, b, s, value]?))
     }
+
+    // Computes the products of challenges for every index in O(n) by doubling
+    fn challenge_exponents(x_s: &[E::Fr], length: usize) -> Vec<E::Fr> {
+        let mut exponents = vec![E::Fr::one(); length];
+        for (i, x) in x_s.iter().enumerate() {
+            let (low, high) = exponents.split_at_mut(1 << i);
+            high[..low.len()]
+                .par_iter_mut()
+                .zip(low.par_iter())
+                .for_each(|(high, low)| *high = *low * x);
+        }
+        exponents
+    }
+}
+
+// A succinct description of the bilinear group: the generators of G1 and G2
+fn curve_identifier<E: PairingEngine>() -> Result<Vec<u8>, SIPPError> {
+    Ok(to_bytes![
+        E::G1Affine::prime_subgroup_generator(),
+        E::G2Affine::prime_subgroup_generator()
+    ]?)
+}
+
+// Ensures the input vectors and coefficients have equal lengths which are a power of 2
+pub(crate) fn check_lengths<E: PairingEngine>(
+    a: &[E::G1Affine],
+    b: &[E::G2Affine],
+    r_len: usize,
+) -> Result<(), SIPPError> {
+    if a.len() != b.len() {
+        return Err(SIPPError::LengthMismatch(a.len(), b.len()));
+    }
+    if a.len() != r_len {
+        return Err(SIPPError::LengthMismatch(a.len(), r_len));
+    }
+    if !a.len().is_power_of_two() {
+        return Err(SIPPError::LengthNotPowerOfTwo(a.len()));
+    }
+    Ok(())
 }
 
 /// Compute the product of pairings of `r_i * a_i` and `b_i`.
@@ -216,7 +605,8 @@ pub fn product_of_pairings<E: PairingEngine>(a: &[E::G1Affine], b: &[E::G2Affine
 #[cfg(test)]
 mod tests {
     use super::*;
-    use algebra::bls12_377::{Bls12_377, Fr, G1Projective, G2Projective};
+    use crate::rng::FiatShamirRng;
+    use algebra::bls12_377::{Bls12_377, Fq12, Fr, G1Projective, G2Projective};
     use blake2::Blake2s;
 
     #[test]
@@ -241,4 +631,187 @@ mod tests {
         assert!(accept.is_ok());
         assert!(accept.unwrap());
     }
+
+    #[test]
+    fn malformed_inputs_are_rejected() {
+        let mut rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![b"falafel"].unwrap());
+        let mut a = Vec::with_capacity(8);
+        let mut b = Vec::with_capacity(8);
+        let mut r = Vec::with_capacity(8);
+        for _ in 0..8 {
+            a.push(G1Projective::rand(&mut rng).into_affine());
+            b.push(G2Projective::rand(&mut rng).into_affine());
+            r.push(Fr::rand(&mut rng));
+        }
+        let z = product_of_pairings_with_coeffs::<Bls12_377>(&a, &b, &r);
+
+        match SIPP::<Bls12_377, Blake2s>::prove(&a, &b[..4], &r, z) {
+            Err(SIPPError::LengthMismatch(8, 4)) => (),
+            _ => panic!("expected length mismatch"),
+        }
+        match SIPP::<Bls12_377, Blake2s>::prove(&a[..6], &b[..6], &r[..6], z) {
+            Err(SIPPError::LengthNotPowerOfTwo(6)) => (),
+            _ => panic!("expected length not a power of two"),
+        }
+
+        let mut proof = SIPP::<Bls12_377, Blake2s>::prove(&a, &b, &r, z).unwrap();
+        match SIPP::<Bls12_377, Blake2s>::verify(&a, &b, &r[..4], z, &proof) {
+            Err(SIPPError::LengthMismatch(8, 4)) => (),
+            _ => panic!("expected length mismatch"),
+        }
+
+        // Proof with the wrong number of elements
+        proof.gt_elems.pop();
+        assert!(!SIPP::<Bls12_377, Blake2s>::verify(&a, &b, &r, z, &proof).unwrap());
+        proof.gt_elems.clear();
+        assert!(!SIPP::<Bls12_377, Blake2s>::verify(&a, &b, &r, z, &proof).unwrap());
+    }
+
+    #[test]
+    fn structured_coefficients() {
+        let mut rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![b"falafel"].unwrap());
+        let mut a = Vec::with_capacity(16);
+        let mut b = Vec::with_capacity(16);
+        for _ in 0..16 {
+            a.push(G1Projective::rand(&mut rng).into_affine());
+            b.push(G2Projective::rand(&mut rng).into_affine());
+        }
+        let s = Fr::rand(&mut rng);
+        let r = structured_scalar_power(16, &s);
+        let z = product_of_pairings_with_coeffs::<Bls12_377>(&a, &b, &r);
+
+        let proof =
+            SIPP::<Bls12_377, Blake2s>::prove_with_structured_coefficients(&a, &b, &s, z).unwrap();
+        assert!(
+            SIPP::<Bls12_377, Blake2s>::verify_with_structured_coefficients(&a, &b, &s, z, &proof)
+                .unwrap()
+        );
+
+        // Wrong coefficient
+        let wrong_s = Fr::rand(&mut rng);
+        assert!(!SIPP::<Bls12_377, Blake2s>::verify_with_structured_coefficients(
+            &a, &b, &wrong_s, z, &proof
+        )
+        .unwrap());
+        // Transcripts are separated from the variant with arbitrary coefficients
+        assert!(!SIPP::<Bls12_377, Blake2s>::verify(&a, &b, &r, z, &proof).unwrap());
+    }
+
+    #[test]
+    fn batch_verify() {
+        let mut rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![b"falafel"].unwrap());
+        let mut statements = Vec::new();
+        for length in [4, 8, 8].iter() {
+            let mut a = Vec::with_capacity(*length);
+            let mut b = Vec::with_capacity(*length);
+            let mut r = Vec::with_capacity(*length);
+            for _ in 0..*length {
+                a.push(G1Projective::rand(&mut rng).into_affine());
+                b.push(G2Projective::rand(&mut rng).into_affine());
+                r.push(Fr::rand(&mut rng));
+            }
+            let z = product_of_pairings_with_coeffs::<Bls12_377>(&a, &b, &r);
+            statements.push((a, b, r, z));
+        }
+        let proofs = statements
+            .iter()
+            .map(|(a, b, r, z)| SIPP::<Bls12_377, Blake2s>::prove(a, b, r, *z).unwrap())
+            .collect::<Vec<_>>();
+        let mut instances = statements
+            .iter()
+            .zip(&proofs)
+            .map(|((a, b, r, z), proof)| (&a[..], &b[..], &r[..], *z, proof))
+            .collect::<Vec<_>>();
+        assert!(SIPP::<Bls12_377, Blake2s>::batch_verify(&instances, &mut rng).unwrap());
+
+        // One instance with a wrong claimed value
+        instances[1].3 = instances[2].3;
+        assert!(!SIPP::<Bls12_377, Blake2s>::batch_verify(&instances, &mut rng).unwrap());
+
+        // One proof for the wrong instance size
+        instances[1].3 = statements[1].3;
+        instances[1].4 = &proofs[0];
+        assert!(!SIPP::<Bls12_377, Blake2s>::batch_verify(&instances, &mut rng).unwrap());
+
+        // An empty batch is rejected
+        match SIPP::<Bls12_377, Blake2s>::batch_verify(&[], &mut rng) {
+            Err(SIPPError::EmptyInput) => (),
+            _ => panic!("expected empty input"),
+        }
+    }
+
+    #[test]
+    fn proof_serialization() {
+        let mut rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![b"falafel"].unwrap());
+        let mut a = Vec::with_capacity(8);
+        let mut b = Vec::with_capacity(8);
+        let mut r = Vec::with_capacity(8);
+        for _ in 0..8 {
+            a.push(G1Projective::rand(&mut rng).into_affine());
+            b.push(G2Projective::rand(&mut rng).into_affine());
+            r.push(Fr::rand(&mut rng));
+        }
+        let z = product_of_pairings_with_coeffs::<Bls12_377>(&a, &b, &r);
+        let proof = SIPP::<Bls12_377, Blake2s>::prove(&a, &b, &r, z).unwrap();
+        assert_eq!(proof.len(), 3);
+        assert_eq!(proof.iter().count(), 3);
+
+        let mut bytes = Vec::new();
+        proof.serialize(&mut bytes).unwrap();
+        let deserialized_proof = Proof::<Bls12_377>::deserialize(&bytes[..]).unwrap();
+        assert!(proof.iter().eq(deserialized_proof.iter()));
+        assert!(SIPP::<Bls12_377, Blake2s>::verify(&a, &b, &r, z, &deserialized_proof).unwrap());
+
+        // Elements outside of GT are rejected
+        let mut gt_elems = proof.iter().cloned().collect::<Vec<_>>();
+        gt_elems[0].0 = Fq12::rand(&mut rng);
+        let mut bytes = Vec::new();
+        Proof::<Bls12_377>::new(gt_elems).serialize(&mut bytes).unwrap();
+        match Proof::<Bls12_377>::deserialize(&bytes[..]) {
+            Err(SIPPError::NotInSubgroup) => (),
+            _ => panic!("expected subgroup check failure"),
+        }
+    }
+
+    #[test]
+    fn domain_separators_are_encoded_injectively() {
+        let domain = DomainSeparator {
+            protocol: b"protocol",
+            version: 1,
+            curve: b"curve",
+            length: 8,
+        };
+        let mut encodings = vec![to_bytes![domain].unwrap()];
+        for other_domain in [
+            DomainSeparator {
+                protocol: b"other protocol",
+                ..domain
+            },
+            DomainSeparator {
+                version: 2,
+                ..domain
+            },
+            DomainSeparator {
+                curve: b"other curve",
+                ..domain
+            },
+            DomainSeparator {
+                length: 16,
+                ..domain
+            },
+            // Same concatenation of protocol and curve as the original domain
+            DomainSeparator {
+                protocol: b"protocolcurve",
+                curve: b"",
+                ..domain
+            },
+        ]
+        .iter()
+        {
+            encodings.push(to_bytes![other_domain].unwrap());
+        }
+        for (i, encoding) in encodings.iter().enumerate() {
+            assert!(encodings[..i].iter().all(|other| other != encoding));
+        }
+    }
 }
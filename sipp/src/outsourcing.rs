@@ -0,0 +1,171 @@
+use algebra_core::{FromBytes, PairingEngine, ToBytes};
+use digest::Digest;
+use std::{
+    io::{Read, Write},
+    marker::PhantomData,
+};
+
+use crate::{
+    check_lengths, compression::CyclotomicCompression, product_of_pairings_with_coeffs, Proof,
+    SIPPError, SIPP,
+};
+
+/// A request to compute the product of pairings of `r_i * a_i` and `b_i`.
+pub struct PairingProductRequest<E: PairingEngine> {
+    /// The G1 elements.
+    pub a: Vec<E::G1Affine>,
+    /// The G2 elements.
+    pub b: Vec<E::G2Affine>,
+    /// The coefficients of the G1 elements.
+    pub r: Vec<E::Fr>,
+}
+
+impl<E: PairingEngine> PairingProductRequest<E> {
+    /// Serialize the request as its length followed by the vectors.
+    pub fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SIPPError> {
+        check_lengths::<E>(&self.a, &self.b, self.r.len())?;
+        (self.a.len() as u64).write(&mut writer)?;
+        for a in self.a.iter() {
+            a.write(&mut writer)?;
+        }
+        for b in self.b.iter() {
+            b.write(&mut writer)?;
+        }
+        for r in self.r.iter() {
+            r.write(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// Deserialize a request. The group elements are read as is, without curve or subgroup
+    /// checks, since a malformed request only affects the answer of the server.
+    pub fn deserialize<R: Read>(mut reader: R) -> Result<Self, SIPPError> {
+        let len = u64::read(&mut reader)?;
+        if !len.is_power_of_two() {
+            return Err(SIPPError::LengthNotPowerOfTwo(len as usize));
+        }
+        let a = (0..len)
+            .map(|_| E::G1Affine::read(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        let b = (0..len)
+            .map(|_| E::G2Affine::read(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        let r = (0..len)
+            .map(|_| E::Fr::read(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { a, b, r })
+    }
+}
+
+/// The claimed product of pairings of a request along with a SIPP proof of it.
+pub struct PairingProductResponse<E: PairingEngine> {
+    /// The claimed product of pairings.
+    pub value: E::Fqk,
+    /// The proof that `value` is the product of pairings of the request.
+    pub proof: Proof<E>,
+}
+
+impl<E: PairingEngine> PairingProductResponse<E>
+where
+    E::Fqk: CyclotomicCompression,
+{
+    /// Serialize the response with GT elements in cyclotomic compressed form.
+    pub fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SIPPError> {
+        self.value.write_compressed(&mut writer)?;
+        self.proof.serialize(&mut writer)?;
+        Ok(())
+    }
+
+    /// Deserialize a response, checking that every GT element lies in the prime order subgroup.
+    pub fn deserialize<R: Read>(mut reader: R) -> Result<Self, SIPPError> {
+        let value = E::Fqk::read_compressed(&mut reader)?;
+        if !Proof::<E>::in_subgroup(&value) {
+            return Err(SIPPError::NotInSubgroup);
+        }
+        let proof = Proof::deserialize(&mut reader)?;
+        Ok(Self { value, proof })
+    }
+}
+
+/// Verifiable outsourcing of products of pairings: the server answers a request with the product
+/// and a SIPP proof, which the client checks with a single pairing.
+pub struct PairingProductOutsourcing<E: PairingEngine, D: Digest> {
+    _engine: PhantomData<E>,
+    _digest: PhantomData<D>,
+}
+
+impl<E: PairingEngine, D: Digest> PairingProductOutsourcing<E, D> {
+    /// Compute the product of pairings of a request and prove it, as the server.
+    pub fn respond(
+        request: &PairingProductRequest<E>,
+    ) -> Result<PairingProductResponse<E>, SIPPError> {
+        check_lengths::<E>(&request.a, &request.b, request.r.len())?;
+        let value = product_of_pairings_with_coeffs::<E>(&request.a, &request.b, &request.r);
+        let proof = SIPP::<E, D>::prove(&request.a, &request.b, &request.r, value)?;
+        Ok(PairingProductResponse { value, proof })
+    }
+
+    /// Check the response of the server to a request, as the client.
+    pub fn verify_response(
+        request: &PairingProductRequest<E>,
+        response: &PairingProductResponse<E>,
+    ) -> Result<bool, SIPPError> {
+        SIPP::<E, D>::verify(
+            &request.a,
+            &request.b,
+            &request.r,
+            response.value,
+            &response.proof,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::FiatShamirRng;
+    use algebra::bls12_377::{Bls12_377, Fr, G1Projective, G2Projective};
+    use algebra_core::{to_bytes, ProjectiveCurve, UniformRand};
+    use blake2::Blake2s;
+
+    type TestOutsourcing = PairingProductOutsourcing<Bls12_377, Blake2s>;
+
+    #[test]
+    fn outsourcing_roundtrip() {
+        let mut rng = FiatShamirRng::<Blake2s>::from_seed(&to_bytes![b"falafel"].unwrap());
+        let mut a = Vec::with_capacity(16);
+        let mut b = Vec::with_capacity(16);
+        let mut r = Vec::with_capacity(16);
+        for _ in 0..16 {
+            a.push(G1Projective::rand(&mut rng).into_affine());
+            b.push(G2Projective::rand(&mut rng).into_affine());
+            r.push(Fr::rand(&mut rng));
+        }
+        let request = PairingProductRequest::<Bls12_377> { a, b, r };
+
+        // Client to server
+        let mut request_bytes = Vec::new();
+        request.serialize(&mut request_bytes).unwrap();
+        let received_request =
+            PairingProductRequest::<Bls12_377>::deserialize(&request_bytes[..]).unwrap();
+        let response = TestOutsourcing::respond(&received_request).unwrap();
+
+        // Server to client
+        let mut response_bytes = Vec::new();
+        response.serialize(&mut response_bytes).unwrap();
+        let received_response =
+            PairingProductResponse::<Bls12_377>::deserialize(&response_bytes[..]).unwrap();
+        assert!(TestOutsourcing::verify_response(&request, &received_response).unwrap());
+        assert_eq!(
+            received_response.value,
+            product_of_pairings_with_coeffs::<Bls12_377>(&request.a, &request.b, &request.r)
+        );
+
+        // Wrong claimed value
+        let wrong_response = PairingProductResponse {
+            value: received_response.value * &received_response.value,
+            proof: received_response.proof,
+        };
+        assert!(!TestOutsourcing::verify_response(&request, &wrong_response).unwrap());
+    }
+}